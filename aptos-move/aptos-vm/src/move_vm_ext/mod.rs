@@ -14,6 +14,8 @@ pub use crate::move_vm_ext::{
     session::SessionExt,
     vm::{get_max_binary_format_version, get_max_identifier_size, verifier_config, MoveVmExt},
 };
+#[cfg(feature = "testing")]
+pub use crate::move_vm_ext::warm_vm_cache::{WarmVmCache, WarmVmCacheGuard};
 use aptos_types::state_store::state_key::StateKey;
 pub use aptos_types::transaction::user_transaction_context::UserTransactionContext;
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
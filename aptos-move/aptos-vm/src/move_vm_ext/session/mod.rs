@@ -14,6 +14,8 @@ use aptos_table_natives::{NativeTableContext, TableChangeSet};
 use aptos_types::{contract_event::ContractEvent, state_store::state_key::StateKey};
 use aptos_vm_types::{change_set::VMChangeSet, storage::change_set_configs::ChangeSetConfigs};
 use bytes::Bytes;
+#[cfg(feature = "failpoints")]
+use fail::fail_point;
 use move_binary_format::errors::{Location, PartialVMError, PartialVMResult, VMResult};
 use move_core_types::{
     effects::{AccountChanges, Changes, Op as MoveStorageOp},
@@ -109,6 +111,18 @@ impl<'r, 'l> SessionExt<'r, 'l> {
 
         let event_context: NativeEventContext = extensions.remove();
         let events = event_context.into_events();
+        #[cfg(feature = "failpoints")]
+        fail_point!(
+            "aptos_vm::move_vm_ext::session::emit_events",
+            !events.is_empty(),
+            |status| {
+                let status_code = status
+                    .and_then(|code| code.parse::<u64>().ok())
+                    .and_then(|code| StatusCode::try_from(code).ok())
+                    .unwrap_or(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR);
+                Err(PartialVMError::new(status_code).finish(Location::Undefined))
+            }
+        );
 
         let woc = WriteOpConverter::new(self.remote, self.is_storage_slot_metadata_enabled);
 
@@ -22,10 +22,17 @@ use move_core_types::{
 use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+#[cfg(feature = "testing")]
+use {
+    crate::natives,
+    move_core_types::account_address::AccountAddress,
+    std::{cell::RefCell, sync::Arc},
+};
 
 const WARM_VM_CACHE_SIZE: usize = 8;
 
-pub(crate) struct WarmVmCache {
+#[derive(Default)]
+pub struct WarmVmCache {
     cache: RwLock<HashMap<WarmVmId, MoveVM>>,
 }
 
@@ -33,15 +40,50 @@ static WARM_VM_CACHE: Lazy<WarmVmCache> = Lazy::new(|| WarmVmCache {
     cache: RwLock::new(HashMap::new()),
 });
 
+#[cfg(feature = "testing")]
+thread_local! {
+    /// When set, the VMs created on this thread are taken from this cache instead of the
+    /// process-wide one.
+    static SCOPED_WARM_VM_CACHE: RefCell<Option<Arc<WarmVmCache>>> = RefCell::new(None);
+}
+
 impl WarmVmCache {
     pub(crate) fn get_warm_vm(
         native_builder: SafeNativeBuilder,
         vm_config: VMConfig,
         resolver: &impl AptosMoveResolver,
     ) -> VMResult<MoveVM> {
+        #[cfg(feature = "testing")]
+        if let Some(cache) = SCOPED_WARM_VM_CACHE.with(|cache| cache.borrow().clone()) {
+            return cache.get(native_builder, vm_config, resolver);
+        }
+
         WARM_VM_CACHE.get(native_builder, vm_config, resolver)
     }
 
+    /// Makes the VMs created on the current thread come from this cache, rather than from the
+    /// process-wide one, until the returned guard is dropped. This lets tests that write modules
+    /// to storage directly keep the modules loaded by their VMs apart from other tests.
+    #[cfg(feature = "testing")]
+    pub fn enter(self: &Arc<Self>) -> WarmVmCacheGuard {
+        WarmVmCacheGuard {
+            previous: SCOPED_WARM_VM_CACHE.with(|cache| cache.replace(Some(self.clone()))),
+        }
+    }
+
+    /// Like [`WarmVmCache::enter`], but for the rest of the lifetime of the current thread, e.g.,
+    /// when starting the threads of a thread pool.
+    #[cfg(feature = "testing")]
+    pub fn install_on_current_thread(self: &Arc<Self>) {
+        SCOPED_WARM_VM_CACHE.with(|cache| *cache.borrow_mut() = Some(self.clone()));
+    }
+
+    /// Drops the cached VMs, so that modules are loaded from storage again by the next VM.
+    #[cfg(feature = "testing")]
+    pub fn invalidate(&self) {
+        self.cache.write().clear();
+    }
+
     fn get(
         &self,
         mut native_builder: SafeNativeBuilder,
@@ -99,9 +141,24 @@ impl WarmVmCache {
     }
 }
 
+/// Restores the cache that was used by the current thread before [`WarmVmCache::enter`].
+#[cfg(feature = "testing")]
+pub struct WarmVmCacheGuard {
+    previous: Option<Arc<WarmVmCache>>,
+}
+
+#[cfg(feature = "testing")]
+impl Drop for WarmVmCacheGuard {
+    fn drop(&mut self) {
+        SCOPED_WARM_VM_CACHE.with(|cache| *cache.borrow_mut() = self.previous.take());
+    }
+}
+
 #[derive(Eq, Hash, PartialEq)]
 struct WarmVmId {
     natives: Bytes,
+    #[cfg(feature = "testing")]
+    natives_address: AccountAddress,
     vm_config: Bytes,
     core_packages_registry: Option<Bytes>,
     bin_v7_enabled: bool,
@@ -119,6 +176,8 @@ impl WarmVmId {
         };
         Ok(Self {
             natives,
+            #[cfg(feature = "testing")]
+            natives_address: natives::natives_address(),
             vm_config: Self::vm_config_bytes(vm_config),
             core_packages_registry: Self::core_packages_id_bytes(resolver)?,
             bin_v7_enabled: Features::fetch_config(resolver)
@@ -37,12 +37,18 @@ use bytes::Bytes;
 #[cfg(feature = "testing")]
 use move_binary_format::errors::PartialVMResult;
 #[cfg(feature = "testing")]
-use move_core_types::{language_storage::StructTag, value::MoveTypeLayout};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::StructTag,
+    value::MoveTypeLayout,
+};
 use move_vm_runtime::native_functions::NativeFunctionTable;
 #[cfg(feature = "testing")]
+use move_vm_runtime::native_functions::{NativeContext, NativeFunction};
+#[cfg(feature = "testing")]
 use move_vm_types::delayed_values::delayed_field_id::DelayedFieldID;
 #[cfg(feature = "testing")]
 use std::{
+    cell::{Cell, RefCell},
     collections::{BTreeMap, HashSet},
     sync::Arc,
 };
@@ -57,6 +63,76 @@ use {
     once_cell::sync::Lazy,
 };
 
+#[cfg(feature = "testing")]
+thread_local! {
+    /// When set, calls to native functions made on this thread are counted here, keyed by the
+    /// fully qualified name of the native.
+    static NATIVE_CALL_COUNTS: RefCell<Option<BTreeMap<String, usize>>> = RefCell::new(None);
+
+    /// The address the natives created on this thread are registered at.
+    static NATIVES_ADDRESS: Cell<AccountAddress> = Cell::new(CORE_CODE_ADDRESS);
+}
+
+/// Registers the natives created on the current thread at `address` instead of
+/// `CORE_CODE_ADDRESS`, e.g., for a framework published at another address. Returns the address
+/// they were registered at before.
+#[cfg(feature = "testing")]
+pub fn set_natives_address(address: AccountAddress) -> AccountAddress {
+    NATIVES_ADDRESS.with(|natives_address| natives_address.replace(address))
+}
+
+/// Returns the address the natives created on the current thread are registered at.
+#[cfg(feature = "testing")]
+pub fn natives_address() -> AccountAddress {
+    NATIVES_ADDRESS.with(Cell::get)
+}
+
+/// Starts (or stops) counting the native function calls made on the current thread. Stopping
+/// discards the counts that were not taken yet.
+#[cfg(feature = "testing")]
+pub fn count_native_calls(enabled: bool) {
+    NATIVE_CALL_COUNTS.with(|counts| *counts.borrow_mut() = enabled.then(BTreeMap::new));
+}
+
+/// Returns the number of calls to each native function made on the current thread since the
+/// last call, sorted by name, leaving the counting enabled.
+#[cfg(feature = "testing")]
+pub fn take_native_call_counts() -> Vec<(String, usize)> {
+    NATIVE_CALL_COUNTS.with(|counts| {
+        counts
+            .borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    })
+}
+
+/// Wraps the native so that its calls are recorded while [`count_native_calls`] is enabled.
+#[cfg(feature = "testing")]
+fn with_call_counting(
+    address: &AccountAddress,
+    module_name: &Identifier,
+    func_name: &Identifier,
+    native: NativeFunction,
+) -> NativeFunction {
+    let name = format!(
+        "0x{}::{}::{}",
+        address.short_str_lossless(),
+        module_name,
+        func_name
+    );
+    Arc::new(move |context: &mut NativeContext, ty_args, args| {
+        NATIVE_CALL_COUNTS.with(|counts| {
+            if let Some(counts) = counts.borrow_mut().as_mut() {
+                *counts.entry(name.clone()).or_default() += 1;
+            }
+        });
+        native(context, ty_args, args)
+    })
+}
+
 #[cfg(feature = "testing")]
 struct AptosBlankStorage;
 
@@ -169,19 +245,26 @@ pub fn aptos_natives(
 }
 
 pub fn aptos_natives_with_builder(builder: &mut SafeNativeBuilder) -> NativeFunctionTable {
+    #[cfg(feature = "testing")]
+    let address = natives_address();
+    #[cfg(not(feature = "testing"))]
+    let address = CORE_CODE_ADDRESS;
+
     #[allow(unreachable_code)]
-    aptos_move_stdlib::natives::all_natives(CORE_CODE_ADDRESS, builder)
+    let natives = aptos_move_stdlib::natives::all_natives(address, builder)
         .into_iter()
         .filter(|(_, name, _, _)| name.as_str() != "vector")
-        .chain(aptos_framework::natives::all_natives(
-            CORE_CODE_ADDRESS,
-            builder,
-        ))
-        .chain(aptos_table_natives::table_natives(
-            CORE_CODE_ADDRESS,
-            builder,
-        ))
-        .collect()
+        .chain(aptos_framework::natives::all_natives(address, builder))
+        .chain(aptos_table_natives::table_natives(address, builder));
+
+    // Call counting is only available to tests, production natives are left untouched.
+    #[cfg(feature = "testing")]
+    let natives = natives.map(|(address, module_name, func_name, native)| {
+        let native = with_call_counting(&address, &module_name, &func_name, native);
+        (address, module_name, func_name, native)
+    });
+
+    natives.collect()
 }
 
 pub fn assert_no_test_natives(err_msg: &str) {
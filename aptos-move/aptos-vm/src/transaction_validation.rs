@@ -331,11 +331,12 @@ pub(crate) fn run_success_epilogue(
     log_context: &AdapterLogSchema,
     traversal_context: &mut TraversalContext,
 ) -> Result<(), VMStatus> {
-    fail_point!("move_adapter::run_success_epilogue", |_| {
-        Err(VMStatus::error(
-            StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
-            None,
-        ))
+    fail_point!("move_adapter::run_success_epilogue", |status| {
+        let status_code = status
+            .and_then(|code| code.parse::<u64>().ok())
+            .and_then(|code| StatusCode::try_from(code).ok())
+            .unwrap_or(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR);
+        Err(VMStatus::error(status_code, None))
     });
 
     run_epilogue(
@@ -363,6 +363,7 @@ where
     }
 
     fn update_on_validation(
+        &self,
         txn_idx: TxnIndex,
         incarnation: Incarnation,
         valid: bool,
@@ -371,9 +372,17 @@ where
         versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         scheduler: &Scheduler,
     ) -> Result<SchedulerTask, PanicError> {
+        if !valid {
+            if let Some(commit_hook) = &self.transaction_commit_hook {
+                commit_hook.on_conflict_detected(txn_idx);
+            }
+        }
         let aborted = !valid && scheduler.try_abort(txn_idx, incarnation);
 
         if aborted {
+            if let Some(commit_hook) = &self.transaction_commit_hook {
+                commit_hook.on_speculative_abort(txn_idx);
+            }
             Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
             scheduler.finish_abort(txn_idx, incarnation)
         } else {
@@ -446,6 +455,10 @@ where
         while let Some((txn_idx, incarnation)) = scheduler.try_commit() {
             if !Self::validate_commit_ready(txn_idx, versioned_cache, last_input_output)? {
                 // Transaction needs to be re-executed, one final time.
+                if let Some(commit_hook) = &self.transaction_commit_hook {
+                    commit_hook.on_conflict_detected(txn_idx);
+                    commit_hook.on_speculative_abort(txn_idx);
+                }
 
                 Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
                 // We are going to skip reducing validation index here, as we
@@ -780,7 +793,7 @@ where
             scheduler_task = match scheduler_task {
                 SchedulerTask::ValidationTask(txn_idx, incarnation, wave) => {
                     let valid = Self::validate(txn_idx, last_input_output, versioned_cache)?;
-                    Self::update_on_validation(
+                    self.update_on_validation(
                         txn_idx,
                         incarnation,
                         valid,
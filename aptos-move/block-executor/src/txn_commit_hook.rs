@@ -12,6 +12,14 @@ pub trait TransactionCommitHook: Send + Sync {
     fn on_transaction_committed(&self, txn_idx: TxnIndex, output: &Self::Output);
 
     fn on_execution_aborted(&self, txn_idx: TxnIndex);
+
+    /// Called by the parallel executor whenever a speculative execution of the transaction fails
+    /// validation, i.e., it read a value that a lower transaction has since (re-)written.
+    fn on_conflict_detected(&self, _txn_idx: TxnIndex) {}
+
+    /// Called by the parallel executor whenever a speculative execution of the transaction is
+    /// aborted, which means the transaction is going to be re-executed.
+    fn on_speculative_abort(&self, _txn_idx: TxnIndex) {}
 }
 
 pub struct NoOpTransactionCommitHook<T, E> {
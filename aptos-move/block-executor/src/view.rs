@@ -53,6 +53,8 @@ use aptos_vm_types::resolver::{
 };
 use bytes::Bytes;
 use claims::assert_ok;
+#[cfg(feature = "failpoints")]
+use fail::fail_point;
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_core_types::{value::MoveTypeLayout, vm_status::StatusCode};
 use move_vm_types::{
@@ -1022,6 +1024,16 @@ impl<'a, T: Transaction, S: TStateView<Key = T::Key>, X: Executable> LatestView<
     }
 
     fn get_raw_base_value(&self, state_key: &T::Key) -> PartialVMResult<Option<StateValue>> {
+        #[cfg(feature = "failpoints")]
+        fail_point!("block_executor::view::get_raw_base_value", |status| {
+            let status_code = status
+                .and_then(|code| code.parse::<u64>().ok())
+                .and_then(|code| StatusCode::try_from(code).ok())
+                .unwrap_or(StatusCode::STORAGE_ERROR);
+            Err(PartialVMError::new(status_code)
+                .with_message(format!("Injected storage error for {:?}", state_key)))
+        });
+
         let ret = self.base_view.get_state_value(state_key).map_err(|e| {
             PartialVMError::new(StatusCode::STORAGE_ERROR).with_message(format!(
                 "Unexpected storage error for {:?}: {:?}",
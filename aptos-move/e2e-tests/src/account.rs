@@ -5,7 +5,11 @@
 //! Test infrastructure for modeling Aptos accounts.
 
 use crate::gas_costs;
-use aptos_crypto::ed25519::*;
+use aptos_crypto::{
+    ed25519::*,
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    SigningKey,
+};
 use aptos_keygen::KeyGen;
 use aptos_types::{
     access_path::AccessPath,
@@ -31,6 +35,7 @@ pub const DEFAULT_EXPIRATION_TIME: u64 = 4_000_000;
 pub enum AccountPublicKey {
     Ed25519(Ed25519PublicKey),
     Keyless(KeylessPublicKey),
+    MultiEd25519(MultiEd25519PublicKey),
 }
 
 impl AccountPublicKey {
@@ -38,24 +43,45 @@ impl AccountPublicKey {
         match self {
             AccountPublicKey::Ed25519(pk) => pk.to_bytes().to_vec(),
             AccountPublicKey::Keyless(pk) => pk.to_bytes(),
+            AccountPublicKey::MultiEd25519(pk) => pk.to_bytes(),
         }
     }
 
     pub fn as_ed25519(&self) -> Option<Ed25519PublicKey> {
         match self {
             AccountPublicKey::Ed25519(pk) => Some(pk.clone()),
-            AccountPublicKey::Keyless(_) => None,
+            AccountPublicKey::Keyless(_) | AccountPublicKey::MultiEd25519(_) => None,
         }
     }
 
     pub fn as_keyless(&self) -> Option<KeylessPublicKey> {
         match self {
             AccountPublicKey::Keyless(pk) => Some(pk.clone()),
-            AccountPublicKey::Ed25519(_) => None,
+            AccountPublicKey::Ed25519(_) | AccountPublicKey::MultiEd25519(_) => None,
+        }
+    }
+
+    pub fn as_multi_ed25519(&self) -> Option<MultiEd25519PublicKey> {
+        match self {
+            AccountPublicKey::MultiEd25519(pk) => Some(pk.clone()),
+            AccountPublicKey::Ed25519(_) | AccountPublicKey::Keyless(_) => None,
         }
     }
 }
 
+/// Derives the authentication key for the given public key, as it would be stored on the chain
+/// after rotating an account to that key.
+pub fn auth_key_from_public_key(pubkey: &AccountPublicKey) -> Vec<u8> {
+    match pubkey {
+        AccountPublicKey::Ed25519(pk) => AuthenticationKey::ed25519(pk),
+        AccountPublicKey::Keyless(pk) => {
+            AuthenticationKey::any_key(AnyPublicKey::keyless(pk.clone()))
+        },
+        AccountPublicKey::MultiEd25519(pk) => AuthenticationKey::multi_ed25519(pk),
+    }
+    .to_vec()
+}
+
 /// Details about a Aptos account.
 ///
 /// Tests will typically create a set of `Account` instances to run transactions on. This type
@@ -115,6 +141,18 @@ impl Account {
         }
     }
 
+    /// Creates a new k-of-n multisig account controlled by the given multi-ed25519 public key.
+    /// Transactions from this account are signed with
+    /// [`TransactionBuilder::sign_multi_ed25519`].
+    ///
+    /// Like with [`Account::new`], the account returned by this constructor is a purely logical
+    /// entity.
+    /// TODO: Currently stores a dummy SK, as there is no single private key.
+    pub fn with_multi_ed25519_key(pubkey: MultiEd25519PublicKey) -> Self {
+        let addr = AuthenticationKey::multi_ed25519(&pubkey).account_address();
+        Self::new_from_addr(addr, AccountPublicKey::MultiEd25519(pubkey))
+    }
+
     /// Creates a new account with the given addr and key pair
     ///
     /// Like with [`Account::new`], the account returned by this constructor is a purely logical
@@ -185,13 +223,7 @@ impl Account {
     ///
     /// This is the same as the account's address if the keys have never been rotated.
     pub fn auth_key(&self) -> Vec<u8> {
-        match &self.pubkey {
-            AccountPublicKey::Ed25519(pk) => AuthenticationKey::ed25519(pk),
-            AccountPublicKey::Keyless(pk) => {
-                AuthenticationKey::any_key(AnyPublicKey::keyless(pk.clone()))
-            },
-        }
-        .to_vec()
+        auth_key_from_public_key(&self.pubkey)
     }
 
     pub fn transaction(&self) -> TransactionBuilder {
@@ -304,6 +336,29 @@ impl TransactionBuilder {
             .into_inner()
     }
 
+    /// Signs the transaction with a multi-ed25519 authenticator for a sender created with
+    /// [`Account::with_multi_ed25519_key`], using the given private keys along with their
+    /// indices in the sender's public key. Whether enough keys signed is only checked when the
+    /// transaction is validated.
+    pub fn sign_multi_ed25519(
+        self,
+        private_keys: &[(u8, &Ed25519PrivateKey)],
+    ) -> SignedTransaction {
+        let raw_txn = self.raw();
+        let signatures = private_keys
+            .iter()
+            .map(|(index, private_key)| (private_key.sign(&raw_txn).unwrap(), *index))
+            .collect();
+        SignedTransaction::new_multisig(
+            raw_txn,
+            self.sender
+                .pubkey
+                .as_multi_ed25519()
+                .expect("sender must have a multi-ed25519 key"),
+            MultiEd25519Signature::new(signatures).unwrap(),
+        )
+    }
+
     pub fn sign_multi_agent(self) -> SignedTransaction {
         let secondary_signer_addresses: Vec<AccountAddress> = self
             .secondary_signers
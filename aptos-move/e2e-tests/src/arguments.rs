@@ -0,0 +1,143 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for encoding the arguments of Move functions called through
+//! [`FakeExecutor::exec`][crate::executor::FakeExecutor::exec] and friends.
+
+use anyhow::{anyhow, ensure};
+use move_binary_format::{access::ModuleAccess, file_format::SignatureToken, CompiledModule};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::TypeTag,
+    value::{serialize_values, MoveValue},
+};
+
+/// Builds the BCS-encoded `Vec<Vec<u8>>` arguments expected by `exec`/`try_exec` from Rust
+/// values, keeping track of their Move types so they can be checked against the signature of the
+/// called function.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionArgumentBuilder {
+    args: Vec<(TypeTag, MoveValue)>,
+}
+
+impl TransactionArgumentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a `signer` (or `&signer`) argument for the given address.
+    pub fn signer(self, address: AccountAddress) -> Self {
+        self.arg(TypeTag::Signer, MoveValue::Signer(address))
+    }
+
+    pub fn u64(self, value: u64) -> Self {
+        self.arg(TypeTag::U64, MoveValue::U64(value))
+    }
+
+    pub fn bool(self, value: bool) -> Self {
+        self.arg(TypeTag::Bool, MoveValue::Bool(value))
+    }
+
+    pub fn address(self, address: AccountAddress) -> Self {
+        self.arg(TypeTag::Address, MoveValue::Address(address))
+    }
+
+    /// Adds a `vector<u8>` argument.
+    pub fn bytes(self, bytes: Vec<u8>) -> Self {
+        self.arg(
+            TypeTag::Vector(Box::new(TypeTag::U8)),
+            MoveValue::vector_u8(bytes),
+        )
+    }
+
+    /// Adds a `vector<address>` argument.
+    pub fn addresses(self, addresses: Vec<AccountAddress>) -> Self {
+        self.arg(
+            TypeTag::Vector(Box::new(TypeTag::Address)),
+            MoveValue::Vector(addresses.into_iter().map(MoveValue::Address).collect()),
+        )
+    }
+
+    fn arg(mut self, type_tag: TypeTag, value: MoveValue) -> Self {
+        self.args.push((type_tag, value));
+        self
+    }
+
+    /// Returns the Move types of the arguments added so far, in order.
+    pub fn type_tags(&self) -> Vec<TypeTag> {
+        self.args
+            .iter()
+            .map(|(type_tag, _)| type_tag.clone())
+            .collect()
+    }
+
+    /// Checks the arguments against the parameters of `function_name` in `module`.
+    ///
+    /// Fails if the number of arguments differs from the number of parameters, or if an argument
+    /// does not have the type of its parameter. Parameters whose types cannot be built by this
+    /// builder (e.g., structs or generic types) are not checked.
+    pub fn check_against(
+        &self,
+        module: &CompiledModule,
+        function_name: &str,
+    ) -> anyhow::Result<()> {
+        let handle = module
+            .function_handles()
+            .iter()
+            .find(|handle| module.identifier_at(handle.name).as_str() == function_name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Function {} not found in module {}",
+                    function_name,
+                    module.self_id()
+                )
+            })?;
+        let parameters = &module.signature_at(handle.parameters).0;
+        ensure!(
+            parameters.len() == self.args.len(),
+            "{} expects {} arguments, got {}",
+            function_name,
+            parameters.len(),
+            self.args.len()
+        );
+
+        for (idx, (parameter, (type_tag, _))) in parameters.iter().zip(&self.args).enumerate() {
+            if let Some(expected) = parameter_type_tag(parameter) {
+                ensure!(
+                    &expected == type_tag,
+                    "Argument {} of {} has type {}, but the parameter has type {}",
+                    idx,
+                    function_name,
+                    type_tag,
+                    expected
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the BCS-encoded arguments.
+    pub fn build(self) -> Vec<Vec<u8>> {
+        serialize_values(self.args.iter().map(|(_, value)| value))
+    }
+}
+
+/// Converts a parameter type to the type of the argument passed for it, if it is one of the
+/// primitive types supported by [`TransactionArgumentBuilder`]. Signer references are passed as
+/// signers.
+fn parameter_type_tag(token: &SignatureToken) -> Option<TypeTag> {
+    Some(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U16 => TypeTag::U16,
+        SignatureToken::U32 => TypeTag::U32,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::U256 => TypeTag::U256,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Signer => TypeTag::Signer,
+        SignatureToken::Reference(inner) if **inner == SignatureToken::Signer => TypeTag::Signer,
+        SignatureToken::Vector(inner) => TypeTag::Vector(Box::new(parameter_type_tag(inner)?)),
+        _ => return None,
+    })
+}
@@ -9,7 +9,7 @@ use aptos_types::{
     account_config::CoinInfoResource,
     state_store::{
         errors::StateviewError, in_memory_state_view::InMemoryStateView, state_key::StateKey,
-        state_storage_usage::StateStorageUsage, state_value::StateValue, TStateView,
+        state_storage_usage::StateStorageUsage, state_value::StateValue, StateView, TStateView,
     },
     transaction::ChangeSet,
     write_set::{TransactionWrite, WriteSet},
@@ -21,7 +21,10 @@ use aptos_vm_genesis::{
 use move_core_types::language_storage::ModuleId;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
 
 /// Dummy genesis ChangeSet for testing
 pub static GENESIS_CHANGE_SET_HEAD: Lazy<ChangeSet> =
@@ -33,6 +36,14 @@ pub static GENESIS_CHANGE_SET_TESTNET: Lazy<ChangeSet> =
 pub static GENESIS_CHANGE_SET_MAINNET: Lazy<ChangeSet> =
     Lazy::new(|| generate_genesis_change_set_for_mainnet(GenesisOptions::Mainnet));
 
+/// Data store with [`GENESIS_CHANGE_SET_HEAD`] applied, built once and cloned by every executor
+/// starting from the head genesis.
+pub static GENESIS_DATA_STORE_HEAD: Lazy<FakeDataStore> = Lazy::new(|| {
+    let mut data_store = FakeDataStore::default();
+    data_store.add_write_set(GENESIS_CHANGE_SET_HEAD.write_set());
+    data_store
+});
+
 /// An in-memory implementation of `StateView` and `ExecutorView` for the VM.
 ///
 /// Tests use this to set up state, and pass in a reference to the cache whenever a `StateView` or
@@ -40,6 +51,18 @@ pub static GENESIS_CHANGE_SET_MAINNET: Lazy<ChangeSet> =
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct FakeDataStore {
     state_data: HashMap<StateKey, StateValue>,
+    /// Speculative writes layered over `state_data` (`None` marks a deletion), if an overlay was
+    /// started with `begin_overlay`.
+    #[serde(skip)]
+    overlay: Option<HashMap<StateKey, Option<StateValue>>>,
+}
+
+/// Token for the overlay started by [`FakeDataStore::begin_overlay`], consumed when the overlay
+/// is either committed or discarded.
+#[must_use = "the overlay must be committed or discarded"]
+#[derive(Debug)]
+pub struct OverlayHandle {
+    _private: (),
 }
 
 impl FakeDataStore {
@@ -50,12 +73,16 @@ impl FakeDataStore {
                 .into_iter()
                 .map(|(k, v)| (k, StateValue::new_legacy(v.into())))
                 .collect(),
+            overlay: None,
         }
     }
 
     /// Creates a new `FakeDataStore` with the provided initial data.
     pub fn new_with_state_value(data: HashMap<StateKey, StateValue>) -> Self {
-        FakeDataStore { state_data: data }
+        FakeDataStore {
+            state_data: data,
+            overlay: None,
+        }
     }
 
     /// Adds a [`WriteSet`] to this data store.
@@ -72,28 +99,92 @@ impl FakeDataStore {
     ///
     /// Returns the previous data if the key was occupied.
     pub fn set_legacy(&mut self, state_key: StateKey, bytes: Vec<u8>) -> Option<StateValue> {
-        self.state_data
-            .insert(state_key, StateValue::new_legacy(bytes.into()))
+        self.set(state_key, StateValue::new_legacy(bytes.into()))
     }
 
     /// Sets a (key, value) pair within this data store.
     ///
     /// Returns the previous data if the key was occupied.
     pub fn set(&mut self, state_key: StateKey, state_value: StateValue) -> Option<StateValue> {
-        self.state_data.insert(state_key, state_value)
+        match &mut self.overlay {
+            Some(overlay) => {
+                let previous = self.state_data.get(&state_key).cloned();
+                overlay
+                    .insert(state_key, Some(state_value))
+                    .unwrap_or(previous)
+            },
+            None => self.state_data.insert(state_key, state_value),
+        }
+    }
+
+    /// Returns the value stored for the key, looking through the overlay first if there is one.
+    fn get(&self, state_key: &StateKey) -> Option<&StateValue> {
+        match self
+            .overlay
+            .as_ref()
+            .and_then(|overlay| overlay.get(state_key))
+        {
+            Some(value) => value.as_ref(),
+            None => self.state_data.get(state_key),
+        }
     }
 
     /// Checks whether the state_key is in this data store
     ///
     pub fn contains_key(&self, state_key: &StateKey) -> bool {
-        self.state_data.contains_key(state_key)
+        self.get(state_key).is_some()
+    }
+
+    /// Returns an iterator over all `(key, value)` pairs in this data store, in no particular
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&StateKey, &StateValue)> {
+        let overlay = self.overlay.as_ref();
+        overlay
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, value)| value.as_ref().map(|value| (key, value)))
+            .chain(self.state_data.iter().filter(move |(key, _)| {
+                overlay.map_or(true, |overlay| !overlay.contains_key(*key))
+            }))
     }
 
     /// Deletes a key from this data store.
     ///
     /// Returns the previous data if the key was occupied.
     pub fn remove(&mut self, state_key: &StateKey) -> Option<StateValue> {
-        self.state_data.remove(state_key)
+        match &mut self.overlay {
+            Some(overlay) => {
+                let previous = self.state_data.get(state_key).cloned();
+                overlay.insert(state_key.clone(), None).unwrap_or(previous)
+            },
+            None => self.state_data.remove(state_key),
+        }
+    }
+
+    /// Starts buffering all writes to this data store in a copy-on-write overlay, while reads
+    /// still fall through to the underlying data for keys that were not written.
+    ///
+    /// Panics if an overlay is already active, as overlays cannot be nested.
+    pub fn begin_overlay(&mut self) -> OverlayHandle {
+        assert!(self.overlay.is_none(), "An overlay is already active");
+        self.overlay = Some(HashMap::new());
+        OverlayHandle { _private: () }
+    }
+
+    /// Applies the writes buffered in the overlay to the underlying data.
+    pub fn commit_overlay(&mut self, _handle: OverlayHandle) {
+        let overlay = self.overlay.take().expect("No overlay is active");
+        for (state_key, value) in overlay {
+            match value {
+                Some(value) => self.state_data.insert(state_key, value),
+                None => self.state_data.remove(&state_key),
+            };
+        }
+    }
+
+    /// Drops the writes buffered in the overlay, restoring the data as of `begin_overlay`.
+    pub fn discard_overlay(&mut self, _handle: OverlayHandle) {
+        self.overlay.take().expect("No overlay is active");
     }
 
     /// Adds an [`AccountData`] to this data store.
@@ -125,18 +216,85 @@ impl TStateView for FakeDataStore {
     type Key = StateKey;
 
     fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
-        Ok(self.state_data.get(state_key).cloned())
+        Ok(self.get(state_key).cloned())
     }
 
     fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
         let mut usage = StateStorageUsage::new_untracked();
-        for (k, v) in self.state_data.iter() {
+        for (k, v) in self.iter() {
             usage.add_item(k.size() + v.size())
         }
         Ok(usage)
     }
 
     fn as_in_memory_state_view(&self) -> InMemoryStateView {
-        InMemoryStateView::new(self.state_data.clone())
+        InMemoryStateView::new(self.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+    }
+}
+
+/// A `StateView` wrapper that records every key read through it, in the order of the reads.
+pub struct RecordingStateView<'a, S> {
+    inner: &'a S,
+    reads: Mutex<Vec<StateKey>>,
+}
+
+impl<'a, S: StateView> RecordingStateView<'a, S> {
+    pub fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            reads: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Consumes the view, returning the keys that were read, in order.
+    pub fn into_reads(self) -> Vec<StateKey> {
+        self.reads.into_inner().unwrap()
+    }
+}
+
+impl<'a, S: StateView> TStateView for RecordingStateView<'a, S> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        self.reads.lock().unwrap().push(state_key.clone());
+        self.inner.get_state_value(state_key)
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        self.inner.get_usage()
+    }
+}
+
+/// A `StateView` wrapper that counts the distinct keys read through it, ignoring the order of the
+/// reads and repeated reads of the same key.
+pub struct CountingStateView<'a, S> {
+    inner: &'a S,
+    reads: Mutex<HashSet<StateKey>>,
+}
+
+impl<'a, S: StateView> CountingStateView<'a, S> {
+    pub fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            reads: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Returns the number of distinct keys read so far.
+    pub fn read_count(&self) -> usize {
+        self.reads.lock().unwrap().len()
+    }
+}
+
+impl<'a, S: StateView> TStateView for CountingStateView<'a, S> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        self.reads.lock().unwrap().insert(state_key.clone());
+        self.inner.get_state_value(state_key)
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        self.inner.get_usage()
     }
 }
@@ -7,16 +7,23 @@
 use crate::{
     account::{Account, AccountData},
     data_store::{
-        FakeDataStore, GENESIS_CHANGE_SET_HEAD, GENESIS_CHANGE_SET_MAINNET,
-        GENESIS_CHANGE_SET_TESTNET,
+        CountingStateView, FakeDataStore, OverlayHandle, RecordingStateView,
+        GENESIS_CHANGE_SET_HEAD, GENESIS_CHANGE_SET_MAINNET, GENESIS_CHANGE_SET_TESTNET,
+        GENESIS_DATA_STORE_HEAD,
     },
+    gas_costs,
     golden_outputs::GoldenOutputs,
+    limits::{LimitedGasMeter, VmLimits},
 };
 use aptos_abstract_gas_usage::CalibrationAlgebra;
 use aptos_bitvec::BitVec;
-use aptos_block_executor::txn_commit_hook::NoOpTransactionCommitHook;
-use aptos_crypto::HashValue;
-use aptos_framework::ReleaseBundle;
+use aptos_block_executor::txn_commit_hook::TransactionCommitHook;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{
+    bls12381, ed25519::Ed25519PrivateKey, multi_ed25519::MultiEd25519PublicKey, HashValue,
+    PrivateKey, Uniform,
+};
+use aptos_framework::{natives::debug, ReleaseBundle};
 use aptos_gas_algebra::DynamicExpression;
 use aptos_gas_meter::{StandardGasAlgebra, StandardGasMeter};
 use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
@@ -25,64 +32,95 @@ use aptos_gas_schedule::{
     LATEST_GAS_FEATURE_VERSION,
 };
 use aptos_keygen::KeyGen;
+use aptos_logger::warn;
 use aptos_memory_usage_tracker::MemoryTrackedGasMeter;
 use aptos_types::{
+    access_path,
     account_config::{
-        new_block_event_key, AccountResource, CoinInfoResource, CoinStoreResource, NewBlockEvent,
-        CORE_CODE_ADDRESS,
+        new_block_event_key, AccountResource, Aggregator, CoinInfoResource, CoinStoreResource,
+        NewBlockEvent, NewEpochEvent, CORE_CODE_ADDRESS,
     },
     block_executor::config::{
         BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
     },
     block_metadata::BlockMetadata,
+    block_metadata_ext::BlockMetadataExt,
     chain_id::ChainId,
     contract_event::ContractEvent,
+    event::EventKey,
     move_utils::MemberId,
     on_chain_config::{
-        AptosVersion, FeatureFlag, Features, OnChainConfig, TimedFeatureOverride,
-        TimedFeaturesBuilder, ValidatorSet,
+        struct_tag_for_config, ApprovedExecutionHashes, AptosVersion, ConfigID,
+        ConfigurationResource, CurrentTimeMicroseconds, FeatureFlag, Features, GasScheduleV2,
+        OnChainConfig, OnChainConsensusConfig, OnChainExecutionConfig, OnChainJWKConsensusConfig,
+        RandomnessConfigMoveStruct, StorageGasSchedule, TimedFeatureOverride, TimedFeaturesBuilder,
+        ValidatorSet,
+    },
+    randomness::{PerBlockRandomness, RandMetadata, Randomness},
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        state_value::StateValue,
+        StateView, TStateView,
     },
-    state_store::{state_key::StateKey, state_value::StateValue, StateView, TStateView},
     transaction::{
         signature_verified_transaction::{
             into_signature_verified_block, SignatureVerifiedTransaction,
         },
-        BlockOutput, EntryFunction, ExecutionStatus, SignedTransaction, Transaction,
+        BlockOutput, ChangeSet, EntryFunction, ExecutionStatus, SignedTransaction, Transaction,
         TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
-        ViewFunctionOutput,
+        ViewFunctionOutput, WriteSetPayload,
     },
-    vm_status::VMStatus,
-    write_set::WriteSet,
+    vm_status::{DiscardedVMStatus, StatusCode, VMStatus},
+    write_set::{WriteOp, WriteSet},
 };
 use aptos_vm::{
     block_executor::{AptosTransactionOutput, BlockAptosVM},
     data_cache::AsMoveResolver,
     gas::get_gas_parameters,
-    move_vm_ext::{AptosMoveResolver, MoveVmExt, SessionId},
+    move_vm_ext::{AptosMoveResolver, MoveVmExt, SessionExt, SessionId},
     verifier, AptosVM, VMValidator,
 };
-use aptos_vm_genesis::{generate_genesis_change_set_for_testing_with_count, GenesisOptions};
+#[cfg(feature = "testing")]
+use aptos_vm::{
+    move_vm_ext::{WarmVmCache, WarmVmCacheGuard},
+    natives,
+};
+use aptos_vm_genesis::{
+    generate_genesis_change_set_for_testing_with_count, generate_test_genesis_with_validators,
+    GenesisOptions, Validator,
+};
 use aptos_vm_logging::log_schema::AdapterLogSchema;
 use aptos_vm_types::storage::{change_set_configs::ChangeSetConfigs, StorageGasParameters};
 use bytes::Bytes;
+use move_binary_format::{
+    compatibility::Compatibility, errors::VMError, file_format::SignatureToken, normalized,
+    CompiledModule,
+};
 use move_core_types::{
     account_address::AccountAddress,
     identifier::Identifier,
-    language_storage::{ModuleId, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     move_resource::MoveResource,
 };
 use move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage};
-use move_vm_types::gas::UnmeteredGasMeter;
-use serde::Serialize;
+use move_vm_types::gas::{GasMeter, UnmeteredGasMeter};
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    collections::BTreeSet,
-    env,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    env, fmt,
     fs::{self, OpenOptions},
     io::Write,
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{Arc, Mutex},
-    time::Instant,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 static RNG_SEED: [u8; 32] = [9u8; 32];
@@ -103,6 +141,11 @@ pub const TRACE_DIR_OUTPUT: &str = "output";
 
 const POSTFIX: &str = "_should_error";
 
+/// Types (as returned by [`Transaction::type_name`]) of the transactions that are not supported by
+/// the parallel block executor. Blocks containing any of them are never compared against a
+/// parallel execution.
+pub const SEQUENTIAL_ONLY_TRANSACTION_TYPES: &[&str] = &["genesis_transaction"];
+
 /// Maps block number N to the index of the input and output transactions
 pub type TraceSeqMapping = (usize, Vec<usize>, Vec<usize>);
 
@@ -121,6 +164,8 @@ pub struct FakeExecutor {
     data_store: FakeDataStore,
     event_store: Vec<ContractEvent>,
     executor_thread_pool: Arc<rayon::ThreadPool>,
+    /// Modules loaded by the VM, shared by the executions of this executor only.
+    module_cache: ModuleCache,
     block_time: u64,
     executed_output: Option<GoldenOutputs>,
     trace_dir: Option<PathBuf>,
@@ -133,6 +178,525 @@ pub struct FakeExecutor {
     features: Features,
     chain_id: u8,
     allow_block_executor_fallback: bool,
+    /// If set, `new_block` keeps the current block time instead of advancing it.
+    block_time_frozen: bool,
+    /// If set, blocks are started with a block prologue that sets this randomness seed.
+    block_randomness_seed: Option<Vec<u8>>,
+    /// If not set, blocks are never run through the parallel executor for comparison, even in
+    /// the BothComparison mode.
+    parallel_check: bool,
+    /// If set, collects the execution time of each transaction of the most recent block.
+    txn_timings: Option<Arc<Mutex<Vec<Duration>>>>,
+    /// Whether the most recent block was not compared against a parallel execution because it
+    /// contains sequential-only transactions.
+    parallel_comparison_skipped: AtomicBool,
+    /// Number of distinct state keys read by the most recent `execute_transaction`.
+    last_txn_read_count: AtomicUsize,
+    /// The write set most recently passed to `apply_write_set`. Borrowed when the executor was
+    /// created from the cached head genesis, to avoid copying the genesis write set.
+    last_applied_write_set: Option<Cow<'static, WriteSet>>,
+    /// Max gas amount used by the transactions built by convenience helpers such as `transfer`.
+    default_max_gas_amount: u64,
+    /// Gas unit price used by the transactions built by convenience helpers such as `transfer`.
+    default_gas_unit_price: u64,
+    /// Output of Move `debug::print` captured during `exec`, `try_exec` and
+    /// `execute_transaction`, drained by `take_debug_output`.
+    debug_output: Mutex<Vec<String>>,
+    /// If set, every executed transaction is recorded here, see `enable_memory_trace`.
+    memory_trace: Option<Mutex<Vec<TracedTxn>>>,
+    /// If set, the native function calls made by the most recent `exec`, `try_exec` or
+    /// `execute_transaction`, see `collect_native_calls`.
+    #[cfg(feature = "testing")]
+    native_calls: Option<Mutex<Vec<(String, usize)>>>,
+    /// Address the framework modules called by name, e.g. through `exec`, are published at.
+    framework_address: AccountAddress,
+}
+
+/// Event handles stored on an account whose counters can be read with
+/// [`FakeExecutor::read_event_handle_count`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventHandleKind {
+    /// `CoinStore::withdraw_events`, advanced once per outgoing transfer.
+    Sent,
+    /// `CoinStore::deposit_events`, advanced once per incoming transfer.
+    Received,
+    /// `Account::coin_register_events`.
+    CoinRegister,
+    /// `Account::key_rotation_events`.
+    KeyRotation,
+}
+
+/// The difference in a single state value between two executors, as reported by
+/// [`FakeExecutor::diff`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StateDiff {
+    /// The key only exists in the other executor.
+    Added(StateValue),
+    /// The key only exists in this executor.
+    Removed(StateValue),
+    /// The key exists in both executors, with different values.
+    Modified {
+        before: StateValue,
+        after: StateValue,
+    },
+}
+
+/// Outcome of feeding arbitrary bytes to [`FakeExecutor::execute_raw_txn_bytes`].
+#[derive(Debug)]
+pub enum ExecutionClassification {
+    /// The bytes are not a BCS-serialized `SignedTransaction`.
+    DeserializeError(String),
+    /// The transaction was rejected by the validator before execution.
+    ValidationReject(DiscardedVMStatus),
+    /// The transaction passed validation but was discarded during execution.
+    Discard(DiscardedVMStatus),
+    /// The transaction was kept, i.e., it either succeeded or failed with gas charged.
+    Keep(TransactionOutput),
+}
+
+/// Kind of the status of a transaction, as reported in a [`TxnSummary`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TxnStatusKind {
+    Success,
+    OutOfGas,
+    MoveAbort,
+    ExecutionFailure,
+    MiscellaneousError,
+    Discard,
+    Retry,
+}
+
+/// Compact summary of the output of a transaction, as returned by
+/// [`FakeExecutor::execute_block_summary`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxnSummary {
+    /// Index of the transaction in its block.
+    pub index: usize,
+    pub status_kind: TxnStatusKind,
+    pub gas_used: u64,
+    /// The abort code, if the transaction aborted.
+    pub abort_code: Option<u64>,
+    pub num_writes: usize,
+    pub num_events: usize,
+}
+
+impl TxnSummary {
+    fn new(index: usize, output: &TransactionOutput) -> Self {
+        let (status_kind, abort_code) = match output.status() {
+            TransactionStatus::Keep(status) => match status {
+                ExecutionStatus::Success => (TxnStatusKind::Success, None),
+                ExecutionStatus::OutOfGas => (TxnStatusKind::OutOfGas, None),
+                ExecutionStatus::MoveAbort { code, .. } => (TxnStatusKind::MoveAbort, Some(*code)),
+                ExecutionStatus::ExecutionFailure { .. } => (TxnStatusKind::ExecutionFailure, None),
+                ExecutionStatus::MiscellaneousError(_) => (TxnStatusKind::MiscellaneousError, None),
+            },
+            TransactionStatus::Discard(_) => (TxnStatusKind::Discard, None),
+            TransactionStatus::Retry => (TxnStatusKind::Retry, None),
+        };
+        Self {
+            index,
+            status_kind,
+            gas_used: output.gas_used(),
+            abort_code,
+            num_writes: output.write_set().iter().count(),
+            num_events: output.events().len(),
+        }
+    }
+}
+
+impl fmt::Display for TxnSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}: {:?}", self.index, self.status_kind)?;
+        if let Some(abort_code) = self.abort_code {
+            write!(f, " (code {})", abort_code)?;
+        }
+        write!(
+            f,
+            ", {} gas, {} writes, {} events",
+            self.gas_used, self.num_writes, self.num_events
+        )
+    }
+}
+
+/// On-chain configs that parameterize the VM and consensus, read by
+/// [`FakeExecutor::read_onchain_configs`]. Configs updated as a side effect of every block or
+/// epoch, like the current time or the reconfiguration counter, are left out.
+const ONCHAIN_CONFIGS: &[ConfigID] = &[
+    ApprovedExecutionHashes::CONFIG_ID,
+    AptosVersion::CONFIG_ID,
+    ChainId::CONFIG_ID,
+    Features::CONFIG_ID,
+    GasScheduleV2::CONFIG_ID,
+    OnChainConsensusConfig::CONFIG_ID,
+    OnChainExecutionConfig::CONFIG_ID,
+    OnChainJWKConsensusConfig::CONFIG_ID,
+    RandomnessConfigMoveStruct::CONFIG_ID,
+    StorageGasSchedule::CONFIG_ID,
+    ValidatorSet::CONFIG_ID,
+];
+
+/// Framework modules that every genesis must publish, checked by
+/// [`FakeExecutor::validate_genesis`].
+const CORE_FRAMEWORK_MODULES: &[&str] = &[
+    "account",
+    "aptos_coin",
+    "block",
+    "coin",
+    "genesis",
+    "reconfiguration",
+    "stake",
+    "timestamp",
+    "transaction_validation",
+];
+
+/// Structural problems with the genesis state, as reported by [`FakeExecutor::validate_genesis`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GenesisError {
+    MissingModule(ModuleId),
+    MissingValidatorSet,
+    MissingConfig(ConfigID),
+}
+
+impl fmt::Display for GenesisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenesisError::MissingModule(module_id) => {
+                write!(f, "framework module {} is not published", module_id)
+            },
+            GenesisError::MissingValidatorSet => write!(f, "validator set is missing"),
+            GenesisError::MissingConfig(config_id) => {
+                write!(f, "on-chain config {} is missing", config_id)
+            },
+        }
+    }
+}
+
+impl std::error::Error for GenesisError {}
+
+/// Reasons a module blob is rejected as an upgrade, as reported by
+/// [`FakeExecutor::check_upgrade_compatibility`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CompatibilityError {
+    /// The old or the new blob does not deserialize into a module.
+    InvalidModule(StatusCode),
+    /// The new module breaks linking or struct layout compatibility with the old one.
+    Incompatible(StatusCode),
+}
+
+impl fmt::Display for CompatibilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompatibilityError::InvalidModule(status) => {
+                write!(f, "module failed to deserialize: {:?}", status)
+            },
+            CompatibilityError::Incompatible(status) => {
+                write!(f, "module is not a compatible upgrade: {:?}", status)
+            },
+        }
+    }
+}
+
+impl std::error::Error for CompatibilityError {}
+
+/// Records the wall-clock time between consecutive transaction commits of a sequential block
+/// execution, i.e., the time it took to execute each transaction.
+struct TimingCommitHook {
+    last_commit: Mutex<Instant>,
+    timings: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl TimingCommitHook {
+    fn new(timings: Arc<Mutex<Vec<Duration>>>) -> Self {
+        timings.lock().unwrap().clear();
+        Self {
+            last_commit: Mutex::new(Instant::now()),
+            timings,
+        }
+    }
+
+    fn record(&self) {
+        let now = Instant::now();
+        let mut last_commit = self.last_commit.lock().unwrap();
+        self.timings
+            .lock()
+            .unwrap()
+            .push(now.duration_since(*last_commit));
+        *last_commit = now;
+    }
+}
+
+impl TransactionCommitHook for TimingCommitHook {
+    type Output = AptosTransactionOutput;
+
+    fn on_transaction_committed(&self, _txn_idx: u32, _output: &Self::Output) {
+        self.record();
+    }
+
+    fn on_execution_aborted(&self, _txn_idx: u32) {
+        self.record();
+    }
+}
+
+/// Captures the output of the Move debug natives executed on the current thread for as long as
+/// it is alive, and moves it into the executor's buffer when dropped.
+struct DebugOutputCapture<'a> {
+    output: &'a Mutex<Vec<String>>,
+}
+
+impl<'a> DebugOutputCapture<'a> {
+    fn new(output: &'a Mutex<Vec<String>>) -> Self {
+        debug::capture_debug_output(true);
+        Self { output }
+    }
+}
+
+impl Drop for DebugOutputCapture<'_> {
+    fn drop(&mut self) {
+        let captured = debug::take_debug_output();
+        debug::capture_debug_output(false);
+        self.output
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .extend(captured);
+    }
+}
+
+/// Counts the native function calls made on the current thread while it is alive, replacing the
+/// previously collected counts when dropped. Does nothing if native calls are not collected.
+#[cfg(feature = "testing")]
+struct NativeCallCapture<'a> {
+    native_calls: Option<&'a Mutex<Vec<(String, usize)>>>,
+}
+
+#[cfg(feature = "testing")]
+impl<'a> NativeCallCapture<'a> {
+    fn new(native_calls: Option<&'a Mutex<Vec<(String, usize)>>>) -> Self {
+        if native_calls.is_some() {
+            natives::count_native_calls(true);
+        }
+        Self { native_calls }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Drop for NativeCallCapture<'_> {
+    fn drop(&mut self) {
+        if let Some(native_calls) = self.native_calls {
+            let counts = natives::take_native_call_counts();
+            natives::count_native_calls(false);
+            *native_calls
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = counts;
+        }
+    }
+}
+
+/// Registers the natives of the VMs created on the current thread at the given framework address
+/// while it is alive.
+#[cfg(feature = "testing")]
+struct NativesAddressScope {
+    previous: AccountAddress,
+}
+
+#[cfg(feature = "testing")]
+impl NativesAddressScope {
+    fn new(framework_address: AccountAddress) -> Self {
+        Self {
+            previous: natives::set_natives_address(framework_address),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Drop for NativesAddressScope {
+    fn drop(&mut self) {
+        natives::set_natives_address(self.previous);
+    }
+}
+
+/// The VMs, and so the modules they loaded and verified, shared by all the executions of a
+/// [`FakeExecutor`] but not with other executors, so that modules written to its data store
+/// directly only need to be reloaded by its own VMs. Requires the `testing` feature, without which
+/// executions share the VMs cached for the whole process.
+#[derive(Default)]
+struct ModuleCache {
+    #[cfg(feature = "testing")]
+    vms: Arc<WarmVmCache>,
+}
+
+/// Makes the VMs created on the current thread come from a [`ModuleCache`] while it is alive.
+struct ModuleCacheScope {
+    #[cfg(feature = "testing")]
+    _guard: WarmVmCacheGuard,
+}
+
+impl ModuleCache {
+    fn enter(&self) -> ModuleCacheScope {
+        ModuleCacheScope {
+            #[cfg(feature = "testing")]
+            _guard: self.vms.enter(),
+        }
+    }
+
+    /// Creates a thread pool for the parallel block executor, whose threads use this cache.
+    fn new_thread_pool(&self) -> rayon::ThreadPool {
+        let builder = rayon::ThreadPoolBuilder::new().num_threads(num_cpus::get());
+        #[cfg(feature = "testing")]
+        let builder = {
+            let vms = self.vms.clone();
+            builder.start_handler(move |_| vms.install_on_current_thread())
+        };
+        builder.build().unwrap()
+    }
+
+    fn invalidate(&self) {
+        #[cfg(feature = "testing")]
+        self.vms.invalidate();
+    }
+}
+
+/// A transaction recorded by the in-memory trace, see [`FakeExecutor::enable_memory_trace`].
+#[derive(Clone, Debug)]
+pub struct TracedTxn {
+    /// The transaction as it was passed to the executor.
+    pub input: Transaction,
+    /// Keys present in the data store before the block containing the transaction executed.
+    pub pre_state_keys: BTreeSet<StateKey>,
+    /// The output of the transaction, or the error of the block containing it.
+    pub output: Result<TransactionOutput, VMStatus>,
+}
+
+/// A state access made by a transaction, as listed by
+/// [`FakeExecutor::execute_transaction_with_reads_then_writes`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccessOp {
+    Read(StateKey),
+    Write(StateKey),
+}
+
+/// A point in the execution of a transaction at which [`FakeExecutor::set_failpoint`] can
+/// inject a failure.
+#[cfg(feature = "failpoints")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VmFailpoint {
+    /// Reading a value from the storage underneath the block executor.
+    StateRead,
+    /// Running the epilogue of a transaction that executed successfully.
+    Epilogue,
+    /// Finishing a session that emitted events.
+    EventEmit,
+}
+
+#[cfg(feature = "failpoints")]
+impl VmFailpoint {
+    fn name(self) -> &'static str {
+        match self {
+            VmFailpoint::StateRead => "block_executor::view::get_raw_base_value",
+            VmFailpoint::Epilogue => "move_adapter::run_success_epilogue",
+            VmFailpoint::EventEmit => "aptos_vm::move_vm_ext::session::emit_events",
+        }
+    }
+}
+
+/// What happens when execution reaches a [`VmFailpoint`].
+#[cfg(feature = "failpoints")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FailBehavior {
+    /// The failpoint fails with the given status code.
+    Error(StatusCode),
+    /// The failpoint panics with the given message.
+    Panic(String),
+}
+
+/// Clears a failpoint set with [`FakeExecutor::set_failpoint`] when dropped.
+///
+/// The guard holds the global lock of [`fail::FailScenario`], so that tests injecting failures
+/// (including the ones configuring failpoints with `fail::cfg` under a `FailScenario`) run one at
+/// a time.
+#[cfg(feature = "failpoints")]
+#[must_use = "the failpoint is cleared as soon as the guard is dropped"]
+pub struct FailpointGuard {
+    _scenario: fail::FailScenario<'static>,
+}
+
+/// Scheduling statistics of a parallel block execution, as returned by
+/// [`FakeExecutor::execute_transaction_block_parallel_with_stats`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ParallelExecStats {
+    /// Number of speculative executions that were aborted, i.e., had to be re-executed.
+    pub num_reexecutions: usize,
+    /// Number of speculative executions that failed validation because a lower transaction
+    /// wrote to a location they had read.
+    pub num_conflicts: usize,
+    /// Indices of the transactions in the block, in the order they were committed.
+    pub commit_order: Vec<u32>,
+}
+
+/// The result of [`FakeExecutor::execute_transaction_block_resilient`].
+#[derive(Debug)]
+pub struct ResilientBlockOutput {
+    /// The output of the sequential block executor.
+    pub output: Result<Vec<TransactionOutput>, VMStatus>,
+    /// How the parallel block executor diverged from the sequential one, if it did: either the
+    /// message it panicked with, or a description of the first mismatching output.
+    pub parallel_discrepancy: Option<String>,
+}
+
+/// Collects [`ParallelExecStats`] from the callbacks of the parallel block executor.
+struct ParallelStatsCommitHook {
+    stats: Arc<Mutex<ParallelExecStats>>,
+}
+
+impl TransactionCommitHook for ParallelStatsCommitHook {
+    type Output = AptosTransactionOutput;
+
+    fn on_transaction_committed(&self, txn_idx: u32, _output: &Self::Output) {
+        self.stats.lock().unwrap().commit_order.push(txn_idx);
+    }
+
+    fn on_execution_aborted(&self, txn_idx: u32) {
+        self.stats.lock().unwrap().commit_order.push(txn_idx);
+    }
+
+    fn on_conflict_detected(&self, _txn_idx: u32) {
+        self.stats.lock().unwrap().num_conflicts += 1;
+    }
+
+    fn on_speculative_abort(&self, _txn_idx: u32) {
+        self.stats.lock().unwrap().num_reexecutions += 1;
+    }
+}
+
+/// Pairs of transactions in a block that access overlapping state, as estimated by
+/// [`FakeExecutor::analyze_block_conflicts`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConflictGraph {
+    /// For each conflicting pair of transaction indices `(i, j)` with `i < j`, the keys that one
+    /// of the two transactions writes and the other one reads or writes.
+    pub edges: BTreeMap<(usize, usize), BTreeSet<StateKey>>,
+}
+
+impl ConflictGraph {
+    /// Returns the indices of the transactions that conflict with the one at `txn_idx`.
+    pub fn conflicts_of(&self, txn_idx: usize) -> Vec<usize> {
+        self.edges
+            .keys()
+            .filter_map(|&(i, j)| {
+                if i == txn_idx {
+                    Some(j)
+                } else if j == txn_idx {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns true if no two transactions of the block access overlapping state.
+    pub fn is_conflict_free(&self) -> bool {
+        self.edges.is_empty()
+    }
 }
 
 pub enum GasMeterType {
@@ -149,16 +713,20 @@ pub enum ExecFuncTimerDynamicArgs {
 impl FakeExecutor {
     /// Creates an executor from a genesis [`WriteSet`].
     pub fn from_genesis(write_set: &WriteSet, chain_id: ChainId) -> Self {
-        let executor_thread_pool = Arc::new(
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(num_cpus::get())
-                .build()
-                .unwrap(),
-        );
-        let mut executor = FakeExecutor {
-            data_store: FakeDataStore::default(),
+        let mut executor = Self::from_data_store(FakeDataStore::default(), chain_id);
+        executor.apply_write_set(write_set);
+        executor
+    }
+
+    /// Creates an executor on top of an already populated data store.
+    fn from_data_store(data_store: FakeDataStore, chain_id: ChainId) -> Self {
+        let module_cache = ModuleCache::default();
+        let executor_thread_pool = Arc::new(module_cache.new_thread_pool());
+        FakeExecutor {
+            data_store,
             event_store: Vec::new(),
             executor_thread_pool,
+            module_cache,
             block_time: 0,
             executed_output: None,
             trace_dir: None,
@@ -167,9 +735,21 @@ impl FakeExecutor {
             features: Features::default(),
             chain_id: chain_id.id(),
             allow_block_executor_fallback: true,
-        };
-        executor.apply_write_set(write_set);
-        executor
+            block_time_frozen: false,
+            block_randomness_seed: None,
+            parallel_check: true,
+            txn_timings: None,
+            parallel_comparison_skipped: AtomicBool::new(false),
+            last_txn_read_count: AtomicUsize::new(0),
+            last_applied_write_set: None,
+            default_max_gas_amount: gas_costs::TXN_RESERVED,
+            default_gas_unit_price: 0,
+            debug_output: Mutex::new(Vec::new()),
+            memory_trace: None,
+            #[cfg(feature = "testing")]
+            native_calls: None,
+            framework_address: CORE_CODE_ADDRESS,
+        }
     }
 
     pub fn set_executor_mode(mut self, mode: ExecutorMode) -> Self {
@@ -189,13 +769,98 @@ impl FakeExecutor {
         self.set_executor_mode(ExecutorMode::BothComparison)
     }
 
+    /// Enables or disables the comparison run with the parallel block executor. Enabled by
+    /// default; disabling it skips both the parallel execution and the equality check against
+    /// the sequential output, which halves the execution time of large suites.
+    pub fn set_parallel_check(&mut self, enabled: bool) {
+        self.parallel_check = enabled;
+    }
+
+    /// Enables or disables collecting the execution time of each transaction in a block. When
+    /// disabled (the default), no timing hooks are installed in the block executor.
+    pub fn collect_timings(&mut self, enabled: bool) {
+        self.txn_timings = enabled.then(|| Arc::new(Mutex::new(Vec::new())));
+    }
+
+    /// Returns the wall-clock execution time of each transaction in the most recently executed
+    /// block, or an empty vector if timing collection is disabled.
+    ///
+    /// Timings are only recorded by the sequential executor, as the parallel executor runs
+    /// transactions concurrently and speculatively. Blocks executed with
+    /// [`ExecutorMode::ParallelOnly`] therefore have no timings.
+    pub fn last_block_timings(&self) -> Vec<Duration> {
+        self.txn_timings
+            .as_ref()
+            .map(|timings| timings.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets the max gas amount and gas unit price used by the transactions built by convenience
+    /// helpers such as [`FakeExecutor::transfer`]. Defaults to the same values as
+    /// [`TransactionBuilder`](crate::account::TransactionBuilder).
+    ///
+    /// These transactions go through the regular, metered, execution path, so the sender is
+    /// charged accordingly. Functions called directly through a session, like `exec` or
+    /// `try_exec`, are unmetered and not affected.
+    pub fn set_default_gas_params(&mut self, max_gas_amount: u64, gas_unit_price: u64) {
+        self.default_max_gas_amount = max_gas_amount;
+        self.default_gas_unit_price = gas_unit_price;
+    }
+
+    /// Makes every subsequent execution that reaches `point` fail with `behavior`, until the
+    /// returned guard is dropped.
+    ///
+    /// Failpoints are global to the process rather than to this executor, so the guard holds a
+    /// global lock: setting a failpoint waits for the guards of other tests to be dropped, and
+    /// must not be done again by the same test while it still holds a guard (or a
+    /// [`fail::FailScenario`]). Requires the `failpoints` feature, which compiles the failpoints
+    /// into the VM and the block executor.
+    #[cfg(feature = "failpoints")]
+    pub fn set_failpoint(&mut self, point: VmFailpoint, behavior: FailBehavior) -> FailpointGuard {
+        let scenario = fail::FailScenario::setup();
+        let actions = match behavior {
+            FailBehavior::Error(status_code) => format!("return({})", status_code as u64),
+            FailBehavior::Panic(message) => format!("panic({})", message),
+        };
+        fail::cfg(point.name(), &actions).expect("Failpoint actions must be valid");
+        FailpointGuard {
+            _scenario: scenario,
+        }
+    }
+
     pub fn disable_block_executor_fallback(&mut self) {
         self.allow_block_executor_fallback = false;
     }
 
     /// Creates an executor from the genesis file GENESIS_FILE_LOCATION
     pub fn from_head_genesis() -> Self {
-        Self::from_genesis(GENESIS_CHANGE_SET_HEAD.clone().write_set(), ChainId::test())
+        // Cloning the pre-built store is much cheaper than re-applying the genesis write set.
+        let mut executor = Self::from_data_store(GENESIS_DATA_STORE_HEAD.clone(), ChainId::test());
+        // Same as if the genesis write set had been applied by `from_genesis`.
+        executor.last_applied_write_set = Some(Cow::Borrowed(GENESIS_CHANGE_SET_HEAD.write_set()));
+        executor
+    }
+
+    /// Creates an executor from the genesis file GENESIS_FILE_LOCATION, starting from the
+    /// pre-built head genesis data store shared by all executors. Same as
+    /// [`FakeExecutor::from_head_genesis`].
+    pub fn from_genesis_file() -> Self {
+        Self::from_head_genesis()
+    }
+
+    /// Creates an executor from the head genesis with an account published at sequence number 0
+    /// for each of the given addresses, funded with the given balance.
+    ///
+    /// The accounts use the genesis keypair, so transactions can be signed with
+    /// [`Account::new_genesis_account`].
+    pub fn from_genesis_with_accounts(balances: &[(AccountAddress, u64)]) -> Self {
+        let mut executor = Self::from_head_genesis();
+        for (address, balance) in balances {
+            let account_data =
+                AccountData::with_account(Account::new_genesis_account(*address), *balance, 0);
+            executor.add_account_data(&account_data);
+        }
+        executor
     }
 
     /// Creates an executor from the genesis file GENESIS_FILE_LOCATION
@@ -233,16 +898,13 @@ impl FakeExecutor {
 
     /// Creates an executor in which no genesis state has been applied yet.
     pub fn no_genesis() -> Self {
-        let executor_thread_pool = Arc::new(
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(num_cpus::get())
-                .build()
-                .unwrap(),
-        );
+        let module_cache = ModuleCache::default();
+        let executor_thread_pool = Arc::new(module_cache.new_thread_pool());
         FakeExecutor {
             data_store: FakeDataStore::default(),
             event_store: Vec::new(),
             executor_thread_pool,
+            module_cache,
             block_time: 0,
             executed_output: None,
             trace_dir: None,
@@ -251,6 +913,20 @@ impl FakeExecutor {
             features: Features::default(),
             chain_id: ChainId::test().id(),
             allow_block_executor_fallback: true,
+            block_time_frozen: false,
+            block_randomness_seed: None,
+            parallel_check: true,
+            txn_timings: None,
+            parallel_comparison_skipped: AtomicBool::new(false),
+            last_txn_read_count: AtomicUsize::new(0),
+            last_applied_write_set: None,
+            default_max_gas_amount: gas_costs::TXN_RESERVED,
+            default_gas_unit_price: 0,
+            debug_output: Mutex::new(Vec::new()),
+            memory_trace: None,
+            #[cfg(feature = "testing")]
+            native_calls: None,
+            framework_address: CORE_CODE_ADDRESS,
         }
     }
 
@@ -275,8 +951,7 @@ impl FakeExecutor {
         //  - the e2e test outputs a golden file, and
         //  - the environment variable is properly set
         if let Some(env_trace_dir) = env::var_os(ENV_TRACE_DIR) {
-            let aptos_version = AptosVersion::fetch_config(&self.data_store.as_move_resolver())
-                .map_or(0, |v| v.major);
+            let aptos_version = self.aptos_version();
 
             let trace_dir = Path::new(&env_trace_dir).join(file_name);
             if trace_dir.exists() {
@@ -303,6 +978,42 @@ impl FakeExecutor {
         }
     }
 
+    /// Starts recording every executed transaction in memory, along with the keys of the state
+    /// it executed against and its output. Unlike the `TRACE` directory tracing, this has no
+    /// filesystem side effects. The recorded transactions are retrieved with
+    /// [`FakeExecutor::take_trace`].
+    pub fn enable_memory_trace(&mut self) {
+        self.memory_trace = Some(Mutex::new(Vec::new()));
+    }
+
+    /// Enables (or disables) collecting the native function calls made by `exec`, `try_exec`,
+    /// `exec_script` and `execute_transaction`, which are then returned by
+    /// [`FakeExecutor::last_native_calls`]. Requires the `testing` feature, which makes the VM
+    /// wrap every native to count its calls.
+    #[cfg(feature = "testing")]
+    pub fn collect_native_calls(&mut self, enabled: bool) {
+        self.native_calls = enabled.then(|| Mutex::new(Vec::new()));
+    }
+
+    /// Returns the number of calls to each native function made by the most recent `exec`,
+    /// `try_exec`, `exec_script` or `execute_transaction`, sorted by the fully qualified name of
+    /// the native. Empty if native calls are not collected.
+    #[cfg(feature = "testing")]
+    pub fn last_native_calls(&self) -> Vec<(String, usize)> {
+        self.native_calls
+            .as_ref()
+            .map(|native_calls| native_calls.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the transactions recorded since the in-memory trace was enabled or last taken.
+    pub fn take_trace(&mut self) -> Vec<TracedTxn> {
+        self.memory_trace
+            .as_mut()
+            .map(|trace| std::mem::take(trace.get_mut().unwrap()))
+            .unwrap_or_default()
+    }
+
     /// Creates an executor with only the standard library Move modules published and not other
     /// initialization done.
     pub fn stdlib_only_genesis() -> Self {
@@ -316,12 +1027,211 @@ impl FakeExecutor {
         genesis
     }
 
+    /// Creates an executor from a full testing genesis of the head framework, i.e., with the
+    /// modules published and the on-chain configs (version, features, gas schedule, ...) set,
+    /// but with an empty validator set and no validator accounts.
+    ///
+    /// Without validators there is nobody to propose a block, so `new_block` emits NIL blocks
+    /// which still emit a `NewBlockEvent` but do not advance the block time.
+    pub fn framework_only_genesis() -> Self {
+        let genesis = generate_test_genesis_with_validators(
+            aptos_cached_packages::head_release_bundle(),
+            &[],
+        );
+        Self::from_genesis(genesis.write_set(), ChainId::test())
+    }
+
+    /// Creates an executor with the compiled Move modules (`.mv` files) found under the given
+    /// directory, and its sub-directories, published. Like [`FakeExecutor::stdlib_only_genesis`],
+    /// no other initialization is done.
+    ///
+    /// This is meant to be pointed at the `bytecode_modules` directory of a freshly built
+    /// framework package, to avoid rebuilding the test binary when iterating on Move code.
+    pub fn from_compiled_package_dir(path: &Path) -> anyhow::Result<Self> {
+        let mut genesis = Self::no_genesis();
+        let mut dirs = vec![path.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().map_or(false, |ext| ext == "mv") {
+                    let bytes = fs::read(&path)?;
+                    let module = CompiledModule::deserialize(&bytes).map_err(|e| {
+                        anyhow::anyhow!("Failed to deserialize {}: {:?}", path.display(), e)
+                    })?;
+                    genesis.add_module(&module.self_id(), bytes);
+                }
+            }
+        }
+        Ok(genesis)
+    }
+
+    /// Creates an executor with the modules of the given framework published at
+    /// `framework_address` instead of `CORE_CODE_ADDRESS`, and no other initialization done.
+    ///
+    /// All references to `CORE_CODE_ADDRESS` in the modules are relocated, both the addresses of
+    /// the modules they use and address constants such as `@aptos_framework`, and helpers calling
+    /// framework modules by name (such as `exec`) use the new address. With the `testing` feature,
+    /// the Move functions run directly by the executor (`exec`, `with_session`, ...) have the
+    /// natives registered at `framework_address`. Transactions cannot be executed, as the VM runs
+    /// the prologue and epilogue at `CORE_CODE_ADDRESS`.
+    pub fn custom_genesis_at_address(
+        framework_address: AccountAddress,
+        framework: &ReleaseBundle,
+    ) -> Self {
+        let relocate = |address: &mut AccountAddress| {
+            if *address == CORE_CODE_ADDRESS {
+                *address = framework_address;
+            }
+        };
+        let mut genesis = Self::no_genesis();
+        genesis.framework_address = framework_address;
+        for mut module in framework.compiled_modules() {
+            module.address_identifiers.iter_mut().for_each(relocate);
+            for constant in module.constant_pool.iter_mut() {
+                match &constant.type_ {
+                    SignatureToken::Address => {
+                        let mut address: AccountAddress = bcs::from_bytes(&constant.data)
+                            .expect("Address constant must deserialize");
+                        relocate(&mut address);
+                        constant.data = bcs::to_bytes(&address).unwrap();
+                    },
+                    SignatureToken::Vector(element) if **element == SignatureToken::Address => {
+                        let mut addresses: Vec<AccountAddress> = bcs::from_bytes(&constant.data)
+                            .expect("Address vector constant must deserialize");
+                        addresses.iter_mut().for_each(relocate);
+                        constant.data = bcs::to_bytes(&addresses).unwrap();
+                    },
+                    _ => {},
+                }
+            }
+            let mut bytes = vec![];
+            module
+                .serialize(&mut bytes)
+                .expect("Relocated module must serialize");
+            genesis.add_module(&module.self_id(), bytes);
+        }
+        genesis
+    }
+
     /// Creates fresh genesis from the framework passed in.
     pub fn custom_genesis(framework: &ReleaseBundle, validator_accounts: Option<usize>) -> Self {
         let genesis = aptos_vm_genesis::generate_test_genesis(framework, validator_accounts);
         Self::from_genesis(genesis.0.write_set(), ChainId::test())
     }
 
+    /// Like [`FakeExecutor::custom_genesis`], but checks the resulting state with
+    /// [`FakeExecutor::validate_genesis`], returning the first problem found.
+    pub fn try_custom_genesis(
+        framework: &ReleaseBundle,
+        validator_accounts: Option<usize>,
+    ) -> Result<Self, GenesisError> {
+        let executor = Self::custom_genesis(framework, validator_accounts);
+        executor.validate_genesis()?;
+        Ok(executor)
+    }
+
+    /// Checks that genesis published the core framework modules, the validator set and the key
+    /// on-chain configs, turning a malformed genesis into a descriptive error instead of
+    /// confusing failures later on.
+    pub fn validate_genesis(&self) -> Result<(), GenesisError> {
+        for name in CORE_FRAMEWORK_MODULES {
+            let module_id = self.module(name);
+            if !self
+                .data_store
+                .contains_key(&StateKey::module_id(&module_id))
+            {
+                return Err(GenesisError::MissingModule(module_id));
+            }
+        }
+
+        let resolver = self.data_store.as_move_resolver();
+        if ValidatorSet::fetch_config(&resolver).is_none() {
+            return Err(GenesisError::MissingValidatorSet);
+        }
+        if ConfigurationResource::fetch_config(&resolver).is_none() {
+            return Err(GenesisError::MissingConfig(
+                ConfigurationResource::CONFIG_ID,
+            ));
+        }
+        if AptosVersion::fetch_config(&resolver).is_none() {
+            return Err(GenesisError::MissingConfig(AptosVersion::CONFIG_ID));
+        }
+        if Features::fetch_config(&resolver).is_none() {
+            return Err(GenesisError::MissingConfig(Features::CONFIG_ID));
+        }
+        if GasScheduleV2::fetch_config(&resolver).is_none() {
+            return Err(GenesisError::MissingConfig(GasScheduleV2::CONFIG_ID));
+        }
+        if OnChainConsensusConfig::fetch_config(&resolver).is_none() {
+            return Err(GenesisError::MissingConfig(
+                OnChainConsensusConfig::CONFIG_ID,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Creates fresh genesis from the framework passed in, with the given on-chain configs
+    /// written into the genesis write set before the executor is constructed.
+    ///
+    /// Each config is provided as its [`ConfigID`] together with the BCS bytes of the Move
+    /// resource, and replaces whatever value genesis would otherwise have published.
+    pub fn custom_genesis_with_configs(
+        framework: &ReleaseBundle,
+        validator_accounts: Option<usize>,
+        configs: Vec<(ConfigID, Vec<u8>)>,
+    ) -> Self {
+        let genesis = aptos_vm_genesis::generate_test_genesis(framework, validator_accounts);
+        let mut write_set = genesis.0.write_set().clone().into_mut();
+        for (config_id, bytes) in configs {
+            let state_key =
+                StateKey::resource(&CORE_CODE_ADDRESS, &struct_tag_for_config(config_id))
+                    .expect("failed to create StateKey");
+            write_set.insert((state_key, WriteOp::legacy_creation(bytes.into())));
+        }
+        let write_set = write_set
+            .freeze()
+            .expect("genesis write set with configs must be valid");
+        Self::from_genesis(&write_set, ChainId::test())
+    }
+
+    /// Creates fresh genesis from the framework passed in, with the given accounts as the initial
+    /// validators, each staking the given amount. The stake is also minted to each validator's
+    /// account, which acts as its own operator and voter.
+    ///
+    /// Returns the executor along with the validator accounts, so that tests can sign
+    /// transactions on behalf of the validators.
+    pub fn custom_genesis_with_validators(
+        framework: &ReleaseBundle,
+        validators: Vec<(Account, u64)>,
+    ) -> (Self, Vec<Account>) {
+        // Consensus keys are never used by the executor, so derive them deterministically.
+        let mut rng = StdRng::from_seed(RNG_SEED);
+        let genesis_validators = validators
+            .iter()
+            .map(|(account, stake)| {
+                let consensus_key = bls12381::PrivateKey::generate(&mut rng);
+                Validator {
+                    owner_address: *account.address(),
+                    operator_address: *account.address(),
+                    voter_address: *account.address(),
+                    stake_amount: *stake,
+                    consensus_pubkey: consensus_key.public_key().to_bytes().to_vec(),
+                    proof_of_possession: bls12381::ProofOfPossession::create(&consensus_key)
+                        .to_bytes()
+                        .to_vec(),
+                    network_addresses: vec![],
+                    full_node_network_addresses: vec![],
+                }
+            })
+            .collect::<Vec<_>>();
+        let genesis = generate_test_genesis_with_validators(framework, &genesis_validators);
+        let executor = Self::from_genesis(genesis.write_set(), ChainId::test());
+        let accounts = validators.into_iter().map(|(account, _)| account).collect();
+        (executor, accounts)
+    }
+
     /// Create one instance of [`AccountData`] without saving it to data store.
     pub fn create_raw_account(&mut self) -> Account {
         Account::new_from_seed(&mut self.rng)
@@ -344,6 +1254,50 @@ impl FakeExecutor {
         accounts
     }
 
+    /// Creates a fresh [`Account`] with the given balance at sequence number 0, and publishes it
+    /// to this executor's data store.
+    pub fn new_funded_account(&mut self, balance: u64) -> Account {
+        let account_data = AccountData::new_from_seed(&mut self.rng, balance, 0);
+        self.add_account_data(&account_data);
+        account_data.into_account()
+    }
+
+    /// Creates an [`Account`] with the given balance at sequence number 0, and publishes it to
+    /// this executor's data store.
+    ///
+    /// The key is derived from a hash of the label rather than from the executor's RNG, so the
+    /// same label always maps to the same address, independently of the order accounts are
+    /// created in.
+    pub fn create_named_account(&mut self, label: &str, balance: u64) -> Account {
+        let seed = HashValue::sha3_256_of(label.as_bytes());
+        let account = Account::new_from_seed(&mut KeyGen::from_seed(*seed));
+        let account_data = AccountData::with_account(account, balance, 0);
+        self.add_account_data(&account_data);
+        account_data.into_account()
+    }
+
+    /// Creates a `threshold`-of-`n` multi-ed25519 [`Account`] with the given balance at sequence
+    /// number 0, and publishes it to this executor's data store.
+    ///
+    /// Returns the account along with its `n` private keys, in the order of their indices in the
+    /// account's public key, to be used with
+    /// [`TransactionBuilder::sign_multi_ed25519`][crate::account::TransactionBuilder::sign_multi_ed25519].
+    pub fn create_multisig_account(
+        &mut self,
+        threshold: u8,
+        n: u8,
+        balance: u64,
+    ) -> (Account, Vec<Ed25519PrivateKey>) {
+        let (private_keys, public_keys): (Vec<_>, Vec<_>) =
+            (0..n).map(|_| self.rng.generate_ed25519_keypair()).unzip();
+        let pubkey = MultiEd25519PublicKey::new(public_keys, threshold)
+            .expect("invalid multi-ed25519 threshold");
+        let account_data =
+            AccountData::with_account(Account::with_multi_ed25519_key(pubkey), balance, 0);
+        self.add_account_data(&account_data);
+        (account_data.into_account(), private_keys)
+    }
+
     /// Creates an account for the given static address. This address needs to be static so
     /// we can load regular Move code to there without need to rewrite code addresses.
     pub fn new_account_at(&mut self, addr: AccountAddress) -> Account {
@@ -360,9 +1314,47 @@ impl FakeExecutor {
         data
     }
 
+    /// Starts executing against a copy-on-write overlay of the current state: transactions
+    /// applied from now on only write to the overlay, while reads fall through to the state as
+    /// of this call. Events and the block time are not covered by the overlay.
+    ///
+    /// The overlay is ended with [`FakeExecutor::commit_overlay`] or
+    /// [`FakeExecutor::discard_overlay`]; overlays cannot be nested.
+    pub fn begin_overlay(&mut self) -> OverlayHandle {
+        self.data_store.begin_overlay()
+    }
+
+    /// Applies the writes made since [`FakeExecutor::begin_overlay`] to the underlying state.
+    pub fn commit_overlay(&mut self, handle: OverlayHandle) {
+        self.data_store.commit_overlay(handle)
+    }
+
+    /// Drops the writes made since [`FakeExecutor::begin_overlay`].
+    pub fn discard_overlay(&mut self, handle: OverlayHandle) {
+        self.data_store.discard_overlay(handle)
+    }
+
     /// Applies a [`WriteSet`] to this executor's data store.
     pub fn apply_write_set(&mut self, write_set: &WriteSet) {
         self.data_store.add_write_set(write_set);
+        self.last_applied_write_set = Some(Cow::Owned(write_set.clone()));
+    }
+
+    /// Returns the write set most recently applied through [`FakeExecutor::apply_write_set`],
+    /// e.g. by `execute_and_apply` or the last transaction of `new_block`.
+    pub fn last_applied_write_set(&self) -> Option<&WriteSet> {
+        self.last_applied_write_set.as_deref()
+    }
+
+    /// Applies a BCS-serialized [`ChangeSet`] (e.g., a recorded genesis or governance upgrade
+    /// step) on top of the current state. Can be called repeatedly to replay a chain of
+    /// upgrades.
+    pub fn apply_genesis_blob(&mut self, blob: &[u8]) {
+        let change_set: ChangeSet =
+            bcs::from_bytes(blob).expect("Failed to deserialize the blob into a ChangeSet");
+        let (write_set, events) = change_set.into_inner();
+        self.apply_write_set(&write_set);
+        self.append_events(events);
     }
 
     pub fn append_events(&mut self, events: Vec<ContractEvent>) {
@@ -398,12 +1390,40 @@ impl FakeExecutor {
     ///
     /// Does not do any sort of verification on the module.
     pub fn add_module(&mut self, module_id: &ModuleId, module_blob: Vec<u8>) {
-        self.data_store.add_module(module_id, module_blob)
+        self.data_store.add_module(module_id, module_blob);
+        self.invalidate_module_cache();
     }
 
-    /// Reads the resource `Value` for an account from this executor's data store.
-    pub fn read_account_resource(&self, account: &Account) -> Option<AccountResource> {
-        self.read_account_resource_at_address(account.address())
+    /// Publishes the bundle of modules on behalf of `sender`, which must be the address of every
+    /// module, and applies the resulting write set to the data store. Unlike `add_module`, the
+    /// modules are checked by the VM, e.g., verified, linked and checked for compatibility with
+    /// the modules they upgrade.
+    pub fn publish_module_bundle(
+        &mut self,
+        sender: AccountAddress,
+        modules: Vec<Vec<u8>>,
+    ) -> Result<(), VMError> {
+        let (result, _) = self.with_session(|session| {
+            session.publish_module_bundle(modules, sender, &mut UnmeteredGasMeter)
+        });
+        self.invalidate_module_cache();
+        result
+    }
+
+    /// Makes the VM reload modules from the data store on their next use.
+    ///
+    /// Modules loaded by the VM are cached across the executions of this executor (and only
+    /// this executor), so that repeated executions do not load and verify the same modules
+    /// again. The cache is built from the data store on the first execution, and has to be
+    /// invalidated whenever modules are written to the data store directly, which `add_module`
+    /// and `publish_module_bundle` do automatically.
+    pub fn invalidate_module_cache(&mut self) {
+        self.module_cache.invalidate();
+    }
+
+    /// Reads the resource `Value` for an account from this executor's data store.
+    pub fn read_account_resource(&self, account: &Account) -> Option<AccountResource> {
+        self.read_account_resource_at_address(account.address())
     }
 
     pub fn read_resource<T: MoveResource>(&self, addr: &AccountAddress) -> Option<T> {
@@ -416,6 +1436,287 @@ impl FakeExecutor {
         bcs::from_bytes(&data_blob).ok()
     }
 
+    /// Like [`FakeExecutor::read_resource`], but returns `None` instead of panicking when the
+    /// resource does not exist under the given address.
+    pub fn try_read_resource<T: MoveResource>(&self, addr: &AccountAddress) -> Option<T> {
+        let state_key = StateKey::resource_typed::<T>(addr).expect("failed to create StateKey");
+        let data_blob = self.read_state_value_bytes(&state_key)?;
+        bcs::from_bytes(&data_blob).ok()
+    }
+
+    /// Reads the current counter of the given event handle for an account.
+    ///
+    /// Returns `None` if the resource holding the handle has not been published.
+    pub fn read_event_handle_count(
+        &self,
+        account: &Account,
+        handle: EventHandleKind,
+    ) -> Option<u64> {
+        let addr = account.address();
+        let count = match handle {
+            EventHandleKind::Sent => self
+                .try_read_resource::<CoinStoreResource>(addr)?
+                .withdraw_events()
+                .count(),
+            EventHandleKind::Received => self
+                .try_read_resource::<CoinStoreResource>(addr)?
+                .deposit_events()
+                .count(),
+            EventHandleKind::CoinRegister => self
+                .try_read_resource::<AccountResource>(addr)?
+                .coin_register_events()
+                .count(),
+            EventHandleKind::KeyRotation => self
+                .try_read_resource::<AccountResource>(addr)?
+                .key_rotation_events()
+                .count(),
+        };
+        Some(count)
+    }
+
+    /// Lists every resource stored under the given address, along with its raw BCS bytes, sorted
+    /// by type. Resources stored in resource groups are listed individually.
+    pub fn list_resources(&self, addr: &AccountAddress) -> Vec<(StructTag, Vec<u8>)> {
+        let mut resources = vec![];
+        for (state_key, state_value) in self.data_store.iter() {
+            let access_path = match state_key.inner() {
+                StateKeyInner::AccessPath(access_path) if &access_path.address == addr => {
+                    access_path
+                },
+                _ => continue,
+            };
+            match access_path.get_path() {
+                access_path::Path::Resource(struct_tag) => {
+                    resources.push((struct_tag, state_value.bytes().to_vec()))
+                },
+                access_path::Path::ResourceGroup(_) => {
+                    let group: BTreeMap<StructTag, Vec<u8>> = bcs::from_bytes(state_value.bytes())
+                        .expect("Resource group must be a map of resources");
+                    resources.extend(group);
+                },
+                access_path::Path::Code(_) => {},
+            }
+        }
+        resources.sort_by(|(tag1, _), (tag2, _)| tag1.cmp(tag2));
+        resources
+    }
+
+    /// Lists the ids of every module published under the given address, sorted by name.
+    pub fn list_modules(&self, addr: &AccountAddress) -> Vec<ModuleId> {
+        let mut modules: Vec<_> = self
+            .data_store
+            .iter()
+            .filter_map(|(state_key, _)| Self::module_id_of(state_key))
+            .filter(|module_id| module_id.address() == addr)
+            .collect();
+        modules.sort();
+        modules
+    }
+
+    /// Panics, listing the offending module ids, if the write set of `output` publishes,
+    /// modifies or deletes any module. Ordinary user transactions should only ever write data.
+    pub fn assert_no_module_writes(&self, output: &TransactionOutput) {
+        let module_ids: Vec<_> = output
+            .write_set()
+            .iter()
+            .filter_map(|(state_key, _)| Self::module_id_of(state_key))
+            .collect();
+        assert!(
+            module_ids.is_empty(),
+            "Transaction wrote to modules: {}",
+            module_ids
+                .iter()
+                .map(ModuleId::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    /// Returns the id of the module stored under `state_key`, if it is a module's key.
+    fn module_id_of(state_key: &StateKey) -> Option<ModuleId> {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+                access_path::Path::Code(module_id) => Some(module_id),
+                access_path::Path::Resource(_) | access_path::Path::ResourceGroup(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns a hash of every key and value in the data store. It only depends on the contents
+    /// of the store, so two executions reach the same state if and only if (barring collisions)
+    /// they end up with the same checksum.
+    pub fn state_checksum(&self) -> HashValue {
+        HashValue::sha3_256_of(
+            &bcs::to_bytes(&self.data_store_entries()).expect("State must serialize"),
+        )
+    }
+
+    /// Returns every key in the data store along with the raw bytes of its value, sorted by key.
+    pub fn data_store_entries(&self) -> BTreeMap<StateKey, Vec<u8>> {
+        self.data_store
+            .iter()
+            .map(|(state_key, state_value)| (state_key.clone(), state_value.bytes().to_vec()))
+            .collect()
+    }
+
+    /// Compares the whole data store, one hex-encoded entry per line, against the golden file
+    /// of `test_name` (suffixed with `_store`, so that it does not clash with the file written
+    /// by [`FakeExecutor::set_golden_file`]). Like other golden files, it is regenerated by
+    /// running the test with `UPDATE_GOLDENFILES=1`.
+    pub fn assert_store_matches_golden(&self, test_name: &str) {
+        let file_name = format!("{}_store", test_name.replace(':', "_"));
+        let golden_outputs = GoldenOutputs::new(&file_name);
+        for (state_key, bytes) in self.data_store_entries() {
+            golden_outputs.log_line(&format!("{:?} => {}", state_key, hex::encode(bytes)));
+        }
+    }
+
+    /// Reads the authentication key stored in the `Account` resource under the given address.
+    pub fn read_auth_key(&self, addr: &AccountAddress) -> Option<Vec<u8>> {
+        self.try_read_resource::<AccountResource>(addr)
+            .map(|account| account.authentication_key().to_vec())
+    }
+
+    /// Returns the gas schedule currently stored on chain.
+    pub fn current_gas_schedule(&self) -> GasScheduleV2 {
+        GasScheduleV2::fetch_config(&self.data_store.as_move_resolver())
+            .expect("Unable to retrieve the gas schedule from storage")
+    }
+
+    /// Returns the gas feature version transactions are currently executed with, i.e. the
+    /// feature version of the on-chain gas schedule, or 0 if only a legacy schedule is stored.
+    pub fn gas_feature_version(&self) -> u64 {
+        GasScheduleV2::fetch_config(&self.data_store.as_move_resolver())
+            .map_or(0, |gas_schedule| gas_schedule.feature_version)
+    }
+
+    /// Checks whether the module in `new` is a compatible upgrade of the module in `old`, using
+    /// the compatibility rules the VM enforces when publishing with the default features, i.e.
+    /// with friend functions treated as private.
+    pub fn check_upgrade_compatibility(old: &[u8], new: &[u8]) -> Result<(), CompatibilityError> {
+        let deserialize = |bytes: &[u8]| {
+            CompiledModule::deserialize(bytes)
+                .map_err(|err| CompatibilityError::InvalidModule(err.major_status()))
+        };
+        let old_module = normalized::Module::new(&deserialize(old)?);
+        let new_module = normalized::Module::new(&deserialize(new)?);
+        Compatibility::new(true, true, false)
+            .check(&old_module, &new_module)
+            .map_err(|err| CompatibilityError::Incompatible(err.major_status()))
+    }
+
+    /// Overwrites the maximum transaction size in the on-chain gas schedule. Transactions whose
+    /// raw transaction is larger than `bytes` are then rejected with
+    /// `EXCEEDED_MAX_TRANSACTION_SIZE`, both during validation and execution.
+    pub fn set_max_transaction_size(&mut self, bytes: u64) {
+        let mut gas_schedule = self.current_gas_schedule();
+        let (_, max_transaction_size) = gas_schedule
+            .entries
+            .iter_mut()
+            .find(|(name, _)| name == "txn.max_transaction_size_in_bytes")
+            .expect("The gas schedule must define the maximum transaction size");
+        *max_transaction_size = bytes;
+
+        let state_key =
+            StateKey::on_chain_config::<GasScheduleV2>().expect("failed to create StateKey");
+        self.write_state_value(state_key, bcs::to_bytes(&gas_schedule).unwrap());
+    }
+
+    /// Returns the major version stored in the on-chain `Version` config, or 0 if there is none.
+    pub fn aptos_version(&self) -> u64 {
+        AptosVersion::fetch_config(&self.data_store.as_move_resolver()).map_or(0, |v| v.major)
+    }
+
+    /// Overwrites the on-chain `Version` config, so that subsequent transactions observe the
+    /// given major version.
+    pub fn set_aptos_version(&mut self, major: u64) {
+        let state_key =
+            StateKey::on_chain_config::<AptosVersion>().expect("failed to create StateKey");
+        self.write_state_value(
+            state_key,
+            bcs::to_bytes(&AptosVersion { major }).expect("Version must serialize"),
+        );
+    }
+
+    /// Returns the on-chain `StorageGas` config, if there is one.
+    pub fn storage_gas_params(&self) -> Option<StorageGasSchedule> {
+        StorageGasSchedule::fetch_config(&self.data_store.as_move_resolver())
+    }
+
+    /// Overwrites the on-chain `StorageGas` config, which prices storage reads and writes per
+    /// item and per byte, independently of the instruction gas schedule.
+    ///
+    /// The VM only reads this config under gas feature versions 1 to 9. Later versions take the
+    /// storage prices from the `storage_io_*` entries of the gas schedule, which are then
+    /// overwritten as well. As these versions charge creations like any other write,
+    /// `per_item_create` and `per_byte_create` are ignored by them.
+    pub fn set_storage_gas_params(&mut self, params: StorageGasSchedule) {
+        if self.gas_feature_version() >= 10 {
+            let mut gas_schedule = self.current_gas_schedule();
+            for (name, value) in gas_schedule.entries.iter_mut() {
+                match name.as_str() {
+                    "txn.storage_io_per_state_slot_read" => *value = params.per_item_read,
+                    "txn.storage_io_per_state_byte_read" => *value = params.per_byte_read,
+                    "txn.storage_io_per_state_slot_write" => *value = params.per_item_write,
+                    "txn.storage_io_per_state_byte_write" => *value = params.per_byte_write,
+                    _ => (),
+                }
+            }
+            let state_key =
+                StateKey::on_chain_config::<GasScheduleV2>().expect("failed to create StateKey");
+            self.write_state_value(state_key, bcs::to_bytes(&gas_schedule).unwrap());
+        }
+
+        let state_key =
+            StateKey::on_chain_config::<StorageGasSchedule>().expect("failed to create StateKey");
+        self.write_state_value(
+            state_key,
+            bcs::to_bytes(&params).expect("StorageGas must serialize"),
+        );
+    }
+
+    /// Returns the BCS bytes of each of the on-chain configs that parameterize the VM and
+    /// consensus (such as the features, the gas schedule or the consensus config), keyed by their
+    /// [`ConfigID`]. Configs that are not published are left out.
+    pub fn read_onchain_configs(&self) -> BTreeMap<ConfigID, Vec<u8>> {
+        ONCHAIN_CONFIGS
+            .iter()
+            .filter_map(|config_id| {
+                let state_key =
+                    StateKey::resource(&CORE_CODE_ADDRESS, &struct_tag_for_config(*config_id))
+                        .expect("failed to create StateKey");
+                let bytes = self.read_state_value_bytes(&state_key)?;
+                Some((*config_id, bytes.to_vec()))
+            })
+            .collect()
+    }
+
+    /// Panics, naming every config that was added, removed or changed, if the current on-chain
+    /// configs differ from `expected`, as previously returned by
+    /// [`FakeExecutor::read_onchain_configs`].
+    pub fn assert_configs_match(&self, expected: &BTreeMap<ConfigID, Vec<u8>>) {
+        let actual = self.read_onchain_configs();
+        let config_ids: BTreeSet<_> = expected.keys().chain(actual.keys()).collect();
+        let differences: Vec<_> = config_ids
+            .into_iter()
+            .filter_map(|config_id| {
+                let change = match (expected.get(config_id), actual.get(config_id)) {
+                    (Some(expected), Some(actual)) if expected != actual => "changed",
+                    (Some(_), None) => "removed",
+                    (None, Some(_)) => "added",
+                    _ => return None,
+                };
+                Some(format!("{} {}", config_id.name(), change))
+            })
+            .collect();
+        assert!(
+            differences.is_empty(),
+            "On-chain configs differ: {}",
+            differences.join(", ")
+        );
+    }
+
     /// Reads the resource `Value` for an account under the given address from
     /// this executor's data store.
     pub fn read_account_resource_at_address(
@@ -453,6 +1754,15 @@ impl FakeExecutor {
         self.read_resource(&AccountAddress::ONE)
     }
 
+    /// Reads the current value of an aggregator, such as the one tracking the supply in a
+    /// [`CoinInfoResource`], from this executor's data store.
+    ///
+    /// Returns `None` if the aggregator has never been written to.
+    pub fn read_aggregator(&self, aggregator: &Aggregator) -> Option<u128> {
+        let bytes = self.read_state_value_bytes(&aggregator.state_key())?;
+        Some(bcs::from_bytes(&bytes).expect("aggregator value must be a u128"))
+    }
+
     /// Reads the CoinStore resource value for an account under the given address from this executor's
     /// data store.
     pub fn read_coin_store_resource_at_address(
@@ -478,6 +1788,83 @@ impl FakeExecutor {
         )
     }
 
+    /// Executes the transactions as a block, applying the kept outputs, and re-executes the ones
+    /// that came back with a `Retry` status in subsequent blocks, like block-STM clients do.
+    ///
+    /// Returns the final output of every transaction, in the order they were passed in, or an
+    /// error if some transactions are still retried after `max_rounds` blocks.
+    pub fn execute_block_with_retries(
+        &mut self,
+        txns: Vec<SignedTransaction>,
+        max_rounds: usize,
+    ) -> anyhow::Result<Vec<TransactionOutput>> {
+        let mut outputs: Vec<Option<TransactionOutput>> = vec![None; txns.len()];
+        let mut pending: Vec<(usize, SignedTransaction)> = txns.into_iter().enumerate().collect();
+
+        for _ in 0..max_rounds {
+            if pending.is_empty() {
+                break;
+            }
+            let (indices, block): (Vec<_>, Vec<_>) = pending.into_iter().unzip();
+            let block_outputs = self
+                .execute_block(block.clone())
+                .map_err(|status| anyhow::anyhow!("Block execution failed: {}", status))?;
+
+            pending = vec![];
+            for ((idx, txn), output) in indices.into_iter().zip(block).zip(block_outputs) {
+                match output.status() {
+                    TransactionStatus::Retry => pending.push((idx, txn)),
+                    TransactionStatus::Keep(_) => {
+                        self.apply_write_set(output.write_set());
+                        outputs[idx] = Some(output);
+                    },
+                    TransactionStatus::Discard(_) => outputs[idx] = Some(output),
+                }
+            }
+        }
+
+        anyhow::ensure!(
+            pending.is_empty(),
+            "{} transaction(s) were still retried after {} rounds",
+            pending.len(),
+            max_rounds
+        );
+        Ok(outputs.into_iter().map(Option::unwrap).collect())
+    }
+
+    /// Executes the block and applies the outputs of the kept transactions, returning all outputs
+    /// along with the total gas used by the kept transactions.
+    ///
+    /// Gas fees are burnt, so with a non-zero gas unit price the drop in
+    /// [`FakeExecutor::read_coin_supply`] over the block can be checked against the total.
+    pub fn execute_and_apply_block_with_gas(
+        &mut self,
+        txns: Vec<SignedTransaction>,
+    ) -> (Vec<TransactionOutput>, u64) {
+        let outputs = self
+            .execute_block(txns)
+            .expect("The VM should not fail to startup");
+        let mut total_gas = 0;
+        for output in &outputs {
+            if let TransactionStatus::Keep(_) = output.status() {
+                self.apply_write_set(output.write_set());
+                total_gas += output.gas_used();
+            }
+        }
+        (outputs, total_gas)
+    }
+
+    /// Executes the given block of transactions like [`FakeExecutor::execute_block`], and
+    /// returns a compact summary of the output of each transaction, in order.
+    pub fn execute_block_summary(&self, txn_block: Vec<SignedTransaction>) -> Vec<TxnSummary> {
+        self.execute_block(txn_block)
+            .expect("The VM should not fail to startup")
+            .iter()
+            .enumerate()
+            .map(|(index, output)| TxnSummary::new(index, output))
+            .collect()
+    }
+
     /// Executes the transaction as a singleton block and applies the resulting write set to the
     /// data store. Panics if execution fails
     pub fn execute_and_apply(&mut self, transaction: SignedTransaction) -> TransactionOutput {
@@ -500,6 +1887,214 @@ impl FakeExecutor {
         }
     }
 
+    /// Executes the transaction as a singleton block and returns the executor, with the write set
+    /// applied if the transaction was kept, alongside the output.
+    ///
+    /// Unlike [`FakeExecutor::execute_and_apply`], this does not panic on failed transactions, so
+    /// it can be folded over a sequence of transactions.
+    pub fn step(mut self, transaction: SignedTransaction) -> (Self, TransactionOutput) {
+        let output = self.execute_transaction(transaction);
+        if let TransactionStatus::Keep(_) = output.status() {
+            self.apply_write_set(output.write_set());
+        }
+        (self, output)
+    }
+
+    /// Executes and applies the transaction like [`FakeExecutor::execute_and_apply`], then
+    /// returns the coin balance of each of the given accounts, in order. Accounts without a
+    /// coin store map to `None`.
+    pub fn execute_and_read_balances(
+        &mut self,
+        transaction: SignedTransaction,
+        accounts: &[&Account],
+    ) -> Vec<Option<u64>> {
+        self.execute_and_apply(transaction);
+        accounts
+            .iter()
+            .map(|account| {
+                self.read_coin_store_resource(account)
+                    .map(|coin_store| coin_store.coin())
+            })
+            .collect()
+    }
+
+    /// Executes and applies the transaction like [`FakeExecutor::execute_and_apply`], and panics
+    /// unless the coin balance of each of the given accounts changed by the expected delta.
+    ///
+    /// The net gas fee paid by the sender is added back to its balance before comparing, so the
+    /// expectations only need to account for the coins moved by the transaction itself. Accounts
+    /// without a coin store are treated as having a zero balance.
+    pub fn assert_balance_change(
+        &mut self,
+        transaction: SignedTransaction,
+        expectations: &[(&Account, i128)],
+    ) {
+        let sender = transaction.sender();
+        let gas_unit_price = transaction.gas_unit_price();
+        let balance = |executor: &Self, account: &Account| {
+            let state_key = StateKey::resource_typed::<CoinStoreResource>(account.address())
+                .expect("failed to create StateKey");
+            executor
+                .read_state_value_bytes(&state_key)
+                .map_or(0, |bytes| {
+                    bcs::from_bytes::<CoinStoreResource>(&bytes)
+                        .expect("CoinStore must deserialize")
+                        .coin() as i128
+                })
+        };
+        let before: Vec<_> = expectations
+            .iter()
+            .map(|(account, _)| balance(self, account))
+            .collect();
+
+        let output = self.execute_and_apply(transaction);
+        let storage_fee_refund = output
+            .try_extract_fee_statement()
+            .expect("The fee statement must deserialize")
+            .map_or(0, |fee_statement| fee_statement.storage_fee_refund());
+        let gas_fee =
+            output.gas_used() as i128 * gas_unit_price as i128 - storage_fee_refund as i128;
+
+        let actual: Vec<_> = expectations
+            .iter()
+            .zip(before)
+            .map(|((account, _), before)| {
+                let delta = balance(self, account) - before;
+                if *account.address() == sender {
+                    delta + gas_fee
+                } else {
+                    delta
+                }
+            })
+            .collect();
+        let expected: Vec<_> = expectations.iter().map(|(_, delta)| *delta).collect();
+        assert_eq!(
+            actual, expected,
+            "Balance changes (excluding gas fees) differ from the expected ones"
+        );
+    }
+
+    /// Repeatedly builds a transaction with `make_txn` and executes and applies it like
+    /// [`FakeExecutor::execute_and_apply`], until `cond` holds. Returns the number of
+    /// transactions that were applied, or an error if `cond` still does not hold after
+    /// `max_iters` of them.
+    pub fn execute_until(
+        &mut self,
+        mut make_txn: impl FnMut(&mut Self) -> SignedTransaction,
+        cond: impl Fn(&Self) -> bool,
+        max_iters: usize,
+    ) -> anyhow::Result<usize> {
+        let mut iters = 0;
+        while !cond(self) {
+            if iters == max_iters {
+                anyhow::bail!("Condition does not hold after {} transactions", max_iters);
+            }
+            let txn = make_txn(self);
+            self.execute_and_apply(txn);
+            iters += 1;
+        }
+        Ok(iters)
+    }
+
+    /// Like [`FakeExecutor::execute_and_apply`], but also applies kept transactions that failed
+    /// (e.g., with a Move abort) instead of panicking, mirroring how failed transactions are
+    /// still charged gas on chain.
+    ///
+    /// For failed transactions the VM only keeps the effects of the epilogue in the write set,
+    /// i.e. the sequence number bump and the fee deduction, so nothing else is applied.
+    pub fn execute_and_apply_keep_gas(
+        &mut self,
+        transaction: SignedTransaction,
+    ) -> TransactionOutput {
+        let output = self.execute_transaction(transaction);
+        match output.status() {
+            TransactionStatus::Keep(_) => {
+                self.apply_write_set(output.write_set());
+                output
+            },
+            TransactionStatus::Discard(status) => panic!("transaction discarded with {:?}", status),
+            TransactionStatus::Retry => panic!("transaction status is retry"),
+        }
+    }
+
+    /// Executes a governance-style write set transaction and applies the resulting write set to
+    /// the data store, unless the transaction was discarded.
+    ///
+    /// The write set payload is wrapped in a [`Transaction::GenesisTransaction`], which is how
+    /// direct write sets are submitted to the VM.
+    pub fn execute_writeset(&mut self, writeset_payload: WriteSetPayload) -> TransactionOutput {
+        let mut outputs = self
+            .execute_transaction_block(vec![Transaction::GenesisTransaction(writeset_payload)])
+            .expect("The VM should not fail to startup");
+        assert!(outputs.len() == 1, "transaction outputs size mismatch");
+        let output = outputs.pop().unwrap();
+        if !output.status().is_discarded() {
+            self.apply_write_set(output.write_set());
+        }
+        output
+    }
+
+    /// Transfers `amount` coins from `from` to `to` using the sender's current sequence number,
+    /// and applies the result. Panics if the transfer does not succeed.
+    pub fn transfer(&mut self, from: &Account, to: &Account, amount: u64) -> TransactionOutput {
+        self.try_transfer(from, to, amount)
+            .unwrap_or_else(|status| {
+                panic!(
+                    "Transfer of {} from {} to {} failed with {:?}",
+                    amount,
+                    from.address(),
+                    to.address(),
+                    status
+                )
+            })
+    }
+
+    /// Builds and signs a transaction sending `payload` from `sender`, with the sender's current
+    /// sequence number, the executor's chain id, the default gas parameters (see
+    /// [`FakeExecutor::set_default_gas_params`]) and an expiration time that never passes.
+    pub fn build_and_sign(
+        &self,
+        sender: &Account,
+        payload: TransactionPayload,
+    ) -> SignedTransaction {
+        let sequence_number = self
+            .read_account_resource(sender)
+            .expect("sender must exist")
+            .sequence_number();
+        sender
+            .transaction()
+            .payload(payload)
+            .sequence_number(sequence_number)
+            .max_gas_amount(self.default_max_gas_amount)
+            .gas_unit_price(self.default_gas_unit_price)
+            .chain_id(ChainId::new(self.chain_id))
+            .ttl(u64::MAX)
+            .sign()
+    }
+
+    /// Like [`FakeExecutor::transfer`], but returns the transaction status instead of panicking
+    /// if the transfer does not succeed. Failed transfers that are kept (e.g., due to an
+    /// insufficient balance) are still applied, so that gas is charged.
+    pub fn try_transfer(
+        &mut self,
+        from: &Account,
+        to: &Account,
+        amount: u64,
+    ) -> Result<TransactionOutput, TransactionStatus> {
+        let txn = self.build_and_sign(
+            from,
+            aptos_stdlib::aptos_coin_transfer(*to.address(), amount),
+        );
+        let output = self.execute_transaction(txn);
+        if let TransactionStatus::Keep(_) = output.status() {
+            self.apply_write_set(output.write_set());
+        }
+        match output.status() {
+            TransactionStatus::Keep(ExecutionStatus::Success) => Ok(output),
+            status => Err(status.clone()),
+        }
+    }
+
     fn execute_transaction_block_impl_with_state_view(
         &self,
         txn_block: &[SignatureVerifiedTransaction],
@@ -507,6 +2102,7 @@ impl FakeExecutor {
         sequential: bool,
         state_view: &(impl StateView + Sync),
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let _module_cache = self.module_cache.enter();
         let config = BlockExecutorConfig {
             local: BlockExecutorLocalConfig {
                 concurrency_level: if sequential {
@@ -519,19 +2115,118 @@ impl FakeExecutor {
             },
             onchain: onchain_config,
         };
-        BlockAptosVM::execute_block::<_, NoOpTransactionCommitHook<AptosTransactionOutput, VMStatus>>(
+        // Per-transaction timings are only meaningful when transactions run one after another.
+        let timing_hook = match &self.txn_timings {
+            Some(timings) if sequential => Some(TimingCommitHook::new(timings.clone())),
+            _ => None,
+        };
+        BlockAptosVM::execute_block::<_, TimingCommitHook>(
             self.executor_thread_pool.clone(),
             txn_block,
             &state_view,
             config,
-            None,
-        ).map(BlockOutput::into_transaction_outputs_forced)
+            timing_hook,
+        )
+        .map(BlockOutput::into_transaction_outputs_forced)
+    }
+
+    /// Executes the block with the parallel executor only (without a sequential fallback or
+    /// comparison) and returns the outputs along with the executor's scheduling statistics, such
+    /// as the number of re-executions and the order in which transactions were committed.
+    pub fn execute_transaction_block_parallel_with_stats(
+        &self,
+        txn_block: Vec<Transaction>,
+    ) -> (Vec<TransactionOutput>, ParallelExecStats) {
+        let _module_cache = self.module_cache.enter();
+        let config = BlockExecutorConfig {
+            local: BlockExecutorLocalConfig {
+                concurrency_level: usize::min(4, num_cpus::get()),
+                // A sequential re-run would make the statistics meaningless.
+                allow_fallback: false,
+                discard_failed_blocks: false,
+            },
+            onchain: BlockExecutorConfigFromOnchain::on_but_large_for_test(),
+        };
+        let stats = Arc::new(Mutex::new(ParallelExecStats::default()));
+        let outputs = BlockAptosVM::execute_block::<_, ParallelStatsCommitHook>(
+            self.executor_thread_pool.clone(),
+            &into_signature_verified_block(txn_block),
+            &self.data_store,
+            config,
+            Some(ParallelStatsCommitHook {
+                stats: stats.clone(),
+            }),
+        )
+        .map(BlockOutput::into_transaction_outputs_forced)
+        .expect("The VM should not fail to startup");
+        let stats = stats.lock().unwrap().clone();
+        (outputs, stats)
+    }
+
+    /// Estimates which transactions of the block conflict, by executing each of them on its own
+    /// against the current state and intersecting the keys they read and write.
+    ///
+    /// Aggregator deltas, such as the coin supply update when burning gas fees, are not counted as
+    /// writes since they do not cause conflicts in the parallel executor. As every transaction is
+    /// run against the current state, accesses that depend on earlier transactions of the block
+    /// are not captured.
+    pub fn analyze_block_conflicts(&self, txns: &[SignedTransaction]) -> ConflictGraph {
+        let _module_cache = self.module_cache.enter();
+        let log_context = AdapterLogSchema::new(self.data_store.id(), 0);
+        let accesses = txns
+            .iter()
+            .map(|txn| {
+                let state_view = RecordingStateView::new(&self.data_store);
+                let writes = {
+                    let resolver = state_view.as_move_resolver();
+                    let vm = AptosVM::new(
+                        &resolver, /*override_is_delayed_field_optimization_capable=*/ None,
+                    );
+                    let (_status, output) =
+                        vm.execute_user_transaction(&resolver, txn, &log_context);
+                    output
+                        .change_set()
+                        .concrete_write_set_iter()
+                        .map(|(key, _)| key.clone())
+                        .collect::<BTreeSet<_>>()
+                };
+                let reads = state_view.into_reads().into_iter().collect::<BTreeSet<_>>();
+                (reads, writes)
+            })
+            .collect::<Vec<_>>();
+
+        let mut edges = BTreeMap::new();
+        for (i, (reads_i, writes_i)) in accesses.iter().enumerate() {
+            for (j, (reads_j, writes_j)) in accesses.iter().enumerate().skip(i + 1) {
+                let keys = writes_i
+                    .iter()
+                    .filter(|key| reads_j.contains(*key) || writes_j.contains(*key))
+                    .chain(writes_j.iter().filter(|key| reads_i.contains(*key)))
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+                if !keys.is_empty() {
+                    edges.insert((i, j), keys);
+                }
+            }
+        }
+        ConflictGraph { edges }
     }
 
     pub fn execute_transaction_block_with_state_view(
         &self,
         txn_block: Vec<Transaction>,
         state_view: &(impl StateView + Sync),
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_transaction_block_with_state_views(txn_block, state_view, state_view)
+    }
+
+    /// Executes the block like [`FakeExecutor::execute_transaction_block_with_state_view`], with
+    /// the sequential and the parallel executor reading from separate state views.
+    fn execute_transaction_block_with_state_views(
+        &self,
+        txn_block: Vec<Transaction>,
+        sequential_state_view: &(impl StateView + Sync),
+        parallel_state_view: &(impl StateView + Sync),
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
         let mut trace_map: (usize, Vec<usize>, Vec<usize>) = TraceSeqMapping::default();
 
@@ -546,15 +2241,50 @@ impl FakeExecutor {
             }
         }
 
+        let memory_trace_inputs = self.memory_trace.as_ref().map(|_| {
+            let pre_state_keys = self
+                .data_store
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect::<BTreeSet<_>>();
+            (txn_block.clone(), pre_state_keys)
+        });
+
+        let sequential_only_txn = txn_block
+            .iter()
+            .map(Transaction::type_name)
+            .find(|type_name| SEQUENTIAL_ONLY_TRANSACTION_TYPES.contains(type_name));
         let sig_verified_block = into_signature_verified_block(txn_block);
 
-        let mode = self.executor_mode.unwrap_or_else(|| {
+        let mut mode = self.executor_mode.unwrap_or_else(|| {
             if env::var(ENV_ENABLE_PARALLEL).is_ok() {
                 ExecutorMode::BothComparison
             } else {
                 ExecutorMode::SequentialOnly
             }
         });
+        if mode == ExecutorMode::BothComparison && !self.parallel_check {
+            mode = ExecutorMode::SequentialOnly;
+        }
+        let mut skip_comparison = false;
+        if mode == ExecutorMode::BothComparison {
+            if let Some(type_name) = sequential_only_txn {
+                warn!(
+                    "Skipping the parallel comparison for a block containing a {}",
+                    type_name
+                );
+                mode = ExecutorMode::SequentialOnly;
+                skip_comparison = true;
+            }
+        }
+        self.parallel_comparison_skipped
+            .store(skip_comparison, Ordering::Relaxed);
+
+        // Only the sequential executor records timings, which must not be left over from a
+        // previous block.
+        if let (ExecutorMode::ParallelOnly, Some(timings)) = (mode, &self.txn_timings) {
+            timings.lock().unwrap().clear();
+        }
 
         // TODO fetch values from state?
         let onchain_config = BlockExecutorConfigFromOnchain::on_but_large_for_test();
@@ -564,7 +2294,7 @@ impl FakeExecutor {
                 &sig_verified_block,
                 onchain_config.clone(),
                 true,
-                state_view,
+                sequential_state_view,
             ))
         } else {
             None
@@ -575,7 +2305,7 @@ impl FakeExecutor {
                 &sig_verified_block,
                 onchain_config,
                 false,
-                state_view,
+                parallel_state_view,
             ))
         } else {
             None
@@ -598,7 +2328,23 @@ impl FakeExecutor {
         let output = sequential_output.or(parallel_output).unwrap();
 
         if let Some(logger) = &self.executed_output {
-            logger.log(format!("{:#?}\n", output).as_str());
+            logger.log_line(format!("{:#?}", output).as_str());
+        }
+
+        if let (Some(memory_trace), Some((inputs, pre_state_keys))) =
+            (&self.memory_trace, memory_trace_inputs)
+        {
+            let mut memory_trace = memory_trace.lock().unwrap();
+            for (idx, input) in inputs.into_iter().enumerate() {
+                memory_trace.push(TracedTxn {
+                    input,
+                    pre_state_keys: pre_state_keys.clone(),
+                    output: output
+                        .as_ref()
+                        .map(|outputs| outputs[idx].clone())
+                        .map_err(Clone::clone),
+                });
+            }
         }
 
         // dump serialized transaction output after execution, if tracing
@@ -633,20 +2379,362 @@ impl FakeExecutor {
         self.execute_transaction_block_with_state_view(txn_block, &self.data_store)
     }
 
-    pub fn execute_transaction(&self, txn: SignedTransaction) -> TransactionOutput {
-        let txn_block = vec![txn];
-        let mut outputs = self
-            .execute_block(txn_block)
-            .expect("The VM should not fail to startup");
-        outputs
+    /// Executes the block with the parallel block executor and then with the sequential one,
+    /// returning the sequential output even if the parallel executor panics or disagrees with it.
+    ///
+    /// Unlike the comparison done by [`FakeExecutor::execute_transaction_block`], this never
+    /// fails the test on a discrepancy, which is instead logged and reported in the returned
+    /// [`ResilientBlockOutput`]. This is meant for bisecting parallel executor crashes.
+    pub fn execute_transaction_block_resilient(
+        &self,
+        txn_block: Vec<Transaction>,
+    ) -> ResilientBlockOutput {
+        let sig_verified_block = into_signature_verified_block(txn_block);
+        let onchain_config = BlockExecutorConfigFromOnchain::on_but_large_for_test();
+
+        let parallel_output = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.execute_transaction_block_impl_with_state_view(
+                &sig_verified_block,
+                onchain_config.clone(),
+                false,
+                &self.data_store,
+            )
+        }));
+        let output = self.execute_transaction_block_impl_with_state_view(
+            &sig_verified_block,
+            onchain_config,
+            true,
+            &self.data_store,
+        );
+
+        let parallel_discrepancy = match parallel_output {
+            Err(payload) => Some(format!(
+                "Parallel execution panicked: {}",
+                payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("<non-string panic payload>")
+            )),
+            Ok(parallel_output) => match (&output, &parallel_output) {
+                (Ok(outputs), Ok(parallel_outputs)) => outputs
+                    .iter()
+                    .zip(parallel_outputs)
+                    .position(|(output, parallel_output)| output != parallel_output)
+                    .map(|idx| format!("Parallel output differs at index {}", idx))
+                    .or_else(|| {
+                        (outputs.len() != parallel_outputs.len()).then(|| {
+                            format!(
+                                "Parallel execution returned {} outputs instead of {}",
+                                parallel_outputs.len(),
+                                outputs.len()
+                            )
+                        })
+                    }),
+                _ if output == parallel_output => None,
+                _ => Some(format!(
+                    "Parallel execution returned {:?} instead of {:?}",
+                    parallel_output, output
+                )),
+            },
+        };
+        if let Some(discrepancy) = &parallel_discrepancy {
+            warn!("{}", discrepancy);
+        }
+
+        ResilientBlockOutput {
+            output,
+            parallel_discrepancy,
+        }
+    }
+
+    pub fn execute_transaction(&self, txn: SignedTransaction) -> TransactionOutput {
+        let _capture = DebugOutputCapture::new(&self.debug_output);
+        #[cfg(feature = "testing")]
+        let _native_calls = NativeCallCapture::new(self.native_calls.as_ref());
+        // Only the sequential run is counted; a parallel run for comparison reads the data store
+        // directly.
+        let state_view = CountingStateView::new(&self.data_store);
+        let mut outputs = self
+            .execute_transaction_block_with_state_views(
+                vec![Transaction::UserTransaction(txn)],
+                &state_view,
+                &self.data_store,
+            )
+            .expect("The VM should not fail to startup");
+        self.last_txn_read_count
+            .store(state_view.read_count(), Ordering::Relaxed);
+        outputs
+            .pop()
+            .expect("A block with one transaction should have one output")
+    }
+
+    /// Executes the transaction like [`FakeExecutor::execute_transaction`], additionally
+    /// returning the keys it read followed by the keys it wrote.
+    ///
+    /// Reads are listed in the order the VM made them, including repeated reads of the same key.
+    /// Writes are not interleaved with them: the VM buffers writes until execution finishes, so
+    /// they are taken from the output's write set and listed after all reads, in key order. The
+    /// transaction is always run by the sequential executor, so the accesses are deterministic.
+    pub fn execute_transaction_with_reads_then_writes(
+        &self,
+        txn: SignedTransaction,
+    ) -> (TransactionOutput, Vec<AccessOp>) {
+        let state_view = RecordingStateView::new(&self.data_store);
+        let output = self
+            .execute_transaction_block_impl_with_state_view(
+                &into_signature_verified_block(vec![Transaction::UserTransaction(txn)]),
+                BlockExecutorConfigFromOnchain::on_but_large_for_test(),
+                true,
+                &state_view,
+            )
+            .expect("The VM should not fail to startup")
+            .pop()
+            .expect("A block with one transaction should have one output");
+
+        let mut accesses = state_view
+            .into_reads()
+            .into_iter()
+            .map(AccessOp::Read)
+            .collect::<Vec<_>>();
+        accesses.extend(
+            output
+                .write_set()
+                .iter()
+                .map(|(key, _)| AccessOp::Write(key.clone())),
+        );
+        (output, accesses)
+    }
+
+    /// Executes the transaction against a minimal state containing only the framework code (the
+    /// modules published at special addresses in this executor's state) and the given key/value
+    /// pairs, e.g. the read-set captured from a production transaction.
+    ///
+    /// Keys that are not provided are treated as non-existent. If the replayed transaction does
+    /// not succeed and read any such key, this panics listing the missing keys, as the failure
+    /// is then likely caused by an incomplete read-set.
+    pub fn replay_against(
+        &self,
+        txn: SignedTransaction,
+        reads: Vec<(StateKey, Vec<u8>)>,
+    ) -> TransactionOutput {
+        let mut data_store = FakeDataStore::default();
+        for (state_key, state_value) in self.data_store.iter() {
+            if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+                if access_path.address.is_special() && access_path.is_code() {
+                    data_store.set(state_key.clone(), state_value.clone());
+                }
+            }
+        }
+        for (state_key, bytes) in reads {
+            data_store.set_legacy(state_key, bytes);
+        }
+
+        let state_view = RecordingStateView::new(&data_store);
+        let output = self
+            .execute_transaction_block_with_state_view(
+                vec![Transaction::UserTransaction(txn)],
+                &state_view,
+            )
+            .expect("The VM should not fail to startup")
+            .pop()
+            .expect("A block with one transaction should have one output");
+
+        let missing_reads = state_view
+            .into_reads()
+            .into_iter()
+            .filter(|state_key| !data_store.contains_key(state_key))
+            .collect::<BTreeSet<_>>();
+        assert!(
+            output.status() == &TransactionStatus::Keep(ExecutionStatus::Success)
+                || missing_reads.is_empty(),
+            "Replay failed with {:?} after reading {} key(s) missing from the captured reads: {:#?}",
+            output.status(),
+            missing_reads.len(),
+            missing_reads
+        );
+        output
+    }
+
+    /// Deserializes the bytes as a `SignedTransaction`, validates it and, if it is accepted,
+    /// executes it against the current state without applying the output.
+    ///
+    /// Malformed input is reported through the returned classification instead of a panic,
+    /// which makes this suitable as the entry point of a fuzz target.
+    pub fn execute_raw_txn_bytes(&self, bytes: &[u8]) -> ExecutionClassification {
+        let txn = match bcs::from_bytes::<SignedTransaction>(bytes) {
+            Ok(txn) => txn,
+            Err(err) => return ExecutionClassification::DeserializeError(err.to_string()),
+        };
+        if let Some(status) = self.validate_transaction(txn.clone()).status() {
+            return ExecutionClassification::ValidationReject(status);
+        }
+
+        match self.execute_transaction_block(vec![Transaction::UserTransaction(txn)]) {
+            Ok(mut outputs) => {
+                let output = outputs
+                    .pop()
+                    .expect("A block with one transaction should have one output");
+                match output.status() {
+                    TransactionStatus::Keep(_) => ExecutionClassification::Keep(output),
+                    TransactionStatus::Discard(status) => ExecutionClassification::Discard(*status),
+                    TransactionStatus::Retry => {
+                        ExecutionClassification::Discard(StatusCode::UNKNOWN_STATUS)
+                    },
+                }
+            },
+            Err(status) => ExecutionClassification::Discard(status.status_code()),
+        }
+    }
+
+    /// Returns the lines printed by Move `debug::print` (and the other debug natives) since the
+    /// previous call, in the order they were printed. Only output from `exec`, `try_exec`,
+    /// `exec_script` and `execute_transaction` is captured.
+    ///
+    /// The capture is per thread: output printed on the worker threads of the parallel block
+    /// executor (see [`ExecutorMode`]) still goes to stdout, so transactions have to be run
+    /// sequentially, as they are by default, for their output to be captured. The debug natives
+    /// only print anything with the `testing` feature.
+    pub fn take_debug_output(&mut self) -> Vec<String> {
+        std::mem::take(
+            self.debug_output
+                .get_mut()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+
+    /// Returns whether the most recent block was executed without being compared against the
+    /// parallel block executor because it contains one of [`SEQUENTIAL_ONLY_TRANSACTION_TYPES`].
+    pub fn parallel_comparison_skipped(&self) -> bool {
+        self.parallel_comparison_skipped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of state reads made while executing the transaction passed to the most
+    /// recent [`FakeExecutor::execute_transaction`] call.
+    ///
+    /// Each distinct key read by the sequential executor is counted once, however often it is
+    /// read. Reads made by the parallel executor are never counted, so the count is zero in
+    /// [`ExecutorMode::ParallelOnly`].
+    pub fn last_txn_read_count(&self) -> usize {
+        self.last_txn_read_count.load(Ordering::Relaxed)
+    }
+
+    /// Executes the transaction against the current state without committing anything to the
+    /// data store, returning the output along with the events it emitted.
+    ///
+    /// Unlike [`FakeExecutor::execute_and_apply`], the write set is never applied, so this can be
+    /// used as a dry-run (e.g., for gas estimation).
+    pub fn simulate(&self, txn: SignedTransaction) -> (TransactionOutput, Vec<ContractEvent>) {
+        let output = self.execute_transaction(txn);
+        let events = output.events().to_vec();
+        (output, events)
+    }
+
+    /// Executes `txn` once per gas schedule, each time against a copy of the current state with
+    /// that schedule installed, and returns the gas used under each schedule, in order.
+    pub fn compare_gas_across_schedules(
+        &self,
+        txn: SignedTransaction,
+        schedules: Vec<GasScheduleV2>,
+    ) -> Vec<u64> {
+        let state_key =
+            StateKey::on_chain_config::<GasScheduleV2>().expect("failed to create StateKey");
+        schedules
+            .into_iter()
+            .map(|schedule| {
+                let mut data_store = self.data_store.clone();
+                data_store.set_legacy(
+                    state_key.clone(),
+                    bcs::to_bytes(&schedule).expect("Gas schedule must serialize"),
+                );
+                self.execute_transaction_block_with_state_view(
+                    vec![Transaction::UserTransaction(txn.clone())],
+                    &data_store,
+                )
+                .expect("The VM should not fail to startup")
+                .pop()
+                .expect("A block with one transaction should have one output")
+                .gas_used()
+            })
+            .collect()
+    }
+
+    /// Executes `txn` against a fresh copy of the current state `runs` times, and panics if the
+    /// status, write set or events of any run differ from those of the first one.
+    pub fn assert_deterministic(&self, txn: SignedTransaction, runs: usize) {
+        let run = || {
+            self.execute_transaction_block_with_state_view(
+                vec![Transaction::UserTransaction(txn.clone())],
+                &self.data_store.clone(),
+            )
+            .expect("The VM should not fail to startup")
             .pop()
             .expect("A block with one transaction should have one output")
+        };
+
+        let expected = run();
+        for idx in 1..runs {
+            let output = run();
+            assert_eq!(
+                output.status(),
+                expected.status(),
+                "Run {} has a different status than run 0",
+                idx
+            );
+            assert_eq!(
+                output.write_set(),
+                expected.write_set(),
+                "Run {} has a different write set than run 0",
+                idx
+            );
+            assert_eq!(
+                output.events(),
+                expected.events(),
+                "Run {} has different events than run 0",
+                idx
+            );
+        }
+    }
+
+    /// Executes the transaction built by `make_txn` for each of the given input sizes against
+    /// the current state, without applying any of them, and panics if the gas used ever
+    /// decreases as the size grows.
+    pub fn assert_gas_monotonic(
+        &self,
+        make_txn: impl Fn(usize) -> SignedTransaction,
+        sizes: &[usize],
+    ) {
+        let gas_used = sizes
+            .iter()
+            .map(|&size| {
+                let output = self.execute_transaction(make_txn(size));
+                assert_eq!(
+                    output.status(),
+                    &TransactionStatus::Keep(ExecutionStatus::Success),
+                    "Transaction of size {} failed",
+                    size
+                );
+                output.gas_used()
+            })
+            .collect::<Vec<_>>();
+
+        for (idx, window) in gas_used.windows(2).enumerate() {
+            assert!(
+                window[0] <= window[1],
+                "Gas used decreased from {} at size {} to {} at size {}",
+                window[0],
+                sizes[idx],
+                window[1],
+                sizes[idx + 1]
+            );
+        }
     }
 
     pub fn execute_transaction_with_gas_profiler(
         &self,
         txn: SignedTransaction,
     ) -> anyhow::Result<(TransactionOutput, TransactionGasLog)> {
+        let _module_cache = self.module_cache.enter();
         let txn = txn
             .check_signature()
             .expect("invalid signature for transaction");
@@ -711,6 +2799,113 @@ impl FakeExecutor {
         seq
     }
 
+    /// Compares the state of this executor against `other`, returning every key whose value
+    /// differs, sorted by key. Values in `self` are treated as the "before" state.
+    pub fn diff(&self, other: &FakeExecutor) -> Vec<(StateKey, StateDiff)> {
+        let keys = self
+            .data_store
+            .iter()
+            .chain(other.data_store.iter())
+            .map(|(k, _)| k)
+            .collect::<BTreeSet<_>>();
+        keys.into_iter()
+            .filter_map(|key| {
+                let diff = match (self.read_state_value(key), other.read_state_value(key)) {
+                    (None, Some(after)) => StateDiff::Added(after),
+                    (Some(before), None) => StateDiff::Removed(before),
+                    (Some(before), Some(after)) if before != after => {
+                        StateDiff::Modified { before, after }
+                    },
+                    _ => return None,
+                };
+                Some((key.clone(), diff))
+            })
+            .collect()
+    }
+
+    /// Asserts that the write set of `output` only touches keys in `allowed`, e.g. the sender's
+    /// account and coin store for sequence number and gas fee updates.
+    pub fn assert_no_state_change_except(&self, output: &TransactionOutput, allowed: &[StateKey]) {
+        let unexpected = output
+            .write_set()
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| !allowed.contains(key))
+            .collect::<Vec<_>>();
+        assert!(
+            unexpected.is_empty(),
+            "Transaction changed unexpected state keys: {:?}",
+            unexpected
+        );
+    }
+
+    /// Asserts that `output` contains an event with the given key whose data deserializes to a
+    /// `T` satisfying `predicate`. On failure, all events emitted by the transaction are listed.
+    pub fn assert_event_emitted<T: DeserializeOwned + Serialize>(
+        &self,
+        output: &TransactionOutput,
+        key: &EventKey,
+        predicate: impl Fn(&T) -> bool,
+    ) {
+        let matched = output
+            .events()
+            .iter()
+            .filter(|event| event.event_key() == Some(key))
+            .filter_map(|event| bcs::from_bytes::<T>(event.event_data()).ok())
+            .any(|data| predicate(&data));
+        assert!(
+            matched,
+            "No event with key {} matched the predicate, emitted events: {:#?}",
+            key,
+            output.events()
+        );
+    }
+
+    /// Writes the BCS-serialized `output` to `path`, to be used as a reference by
+    /// [`FakeExecutor::assert_output_matches_file`].
+    pub fn write_output_to_file(&self, output: &TransactionOutput, path: &Path) {
+        let bytes = bcs::to_bytes(output)
+            .unwrap_or_else(|err| panic!("Failed to serialize the output: {:?}", err));
+        fs::write(path, bytes)
+            .unwrap_or_else(|err| panic!("Failed to write {}: {}", path.display(), err));
+    }
+
+    /// Asserts that `output` matches the reference output stored at `path` by
+    /// [`FakeExecutor::write_output_to_file`], reporting the first field that differs.
+    pub fn assert_output_matches_file(&self, output: &TransactionOutput, path: &Path) {
+        let bytes = fs::read(path)
+            .unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err));
+        let expected: TransactionOutput = bcs::from_bytes(&bytes)
+            .unwrap_or_else(|err| panic!("Failed to deserialize {}: {:?}", path.display(), err));
+
+        assert_eq!(expected.status(), output.status(), "Status differs");
+        assert_eq!(expected.gas_used(), output.gas_used(), "Gas used differs");
+        let keys = expected
+            .write_set()
+            .iter()
+            .chain(output.write_set().iter())
+            .map(|(k, _)| k)
+            .collect::<BTreeSet<_>>();
+        for key in keys {
+            assert_eq!(
+                expected.write_set().get(key),
+                output.write_set().get(key),
+                "Write for {:?} differs",
+                key
+            );
+        }
+        assert_eq!(
+            expected.events().len(),
+            output.events().len(),
+            "Number of events differs"
+        );
+        for (idx, (expected_event, event)) in
+            expected.events().iter().zip(output.events()).enumerate()
+        {
+            assert_eq!(expected_event, event, "Event at index {} differs", idx);
+        }
+    }
+
     pub fn get_events(&self) -> &[ContractEvent] {
         self.event_store.as_slice()
     }
@@ -730,8 +2925,23 @@ impl FakeExecutor {
             .set(state_key, StateValue::new_legacy(data_blob.into()));
     }
 
+    /// Publishes the raw BCS bytes of a resource with the given type under `addr`.
+    ///
+    /// No checks are performed on the bytes, so this can be used to set up states that are hard
+    /// to reach through regular transactions (e.g., partially initialized resources).
+    pub fn store_resource(
+        &mut self,
+        addr: AccountAddress,
+        struct_tag: StructTag,
+        bcs_bytes: Vec<u8>,
+    ) {
+        let state_key = StateKey::resource(&addr, &struct_tag).expect("failed to create StateKey");
+        self.write_state_value(state_key, bcs_bytes);
+    }
+
     /// Verifies the given transaction by running it through the VM verifier.
     pub fn validate_transaction(&self, txn: SignedTransaction) -> VMValidatorResult {
+        let _module_cache = self.module_cache.enter();
         let vm = AptosVM::new(
             &self.get_state_view().as_move_resolver(),
             /*override_is_delayed_field_optimization_capable=*/ None,
@@ -739,12 +2949,90 @@ impl FakeExecutor {
         vm.validate_transaction(txn, &self.data_store)
     }
 
+    /// Checks the transaction's payload against the code publishing and script policy of the
+    /// chain, without executing it.
+    ///
+    /// There is no on-chain publishing option any more: scripts and packages published through
+    /// `code::publish_package_txn` are always allowed, with package upgrades being governed by
+    /// the package's own upgrade policy at execution time. The answer is taken from the VM
+    /// validator, so a payload is rejected exactly when validation discards it with
+    /// `FEATURE_UNDER_GATING`, e.g. the removed module bundle. Other validation failures are not
+    /// a matter of policy and are not reported here.
+    pub fn check_publishing_option(&self, txn: &SignedTransaction) -> Result<(), VMStatus> {
+        match self.validate_transaction(txn.clone()).status() {
+            Some(StatusCode::FEATURE_UNDER_GATING) => {
+                Err(VMStatus::error(StatusCode::FEATURE_UNDER_GATING, None))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Validates a batch of transactions like [`FakeExecutor::validate_transaction`], but with
+    /// a single VM shared by the whole batch. Like mempool, the transactions are validated in
+    /// parallel; the results are returned in input order.
+    pub fn validate_transactions(&self, txns: Vec<SignedTransaction>) -> Vec<VMValidatorResult> {
+        let _module_cache = self.module_cache.enter();
+        let vm = AptosVM::new(
+            &self.get_state_view().as_move_resolver(),
+            /*override_is_delayed_field_optimization_capable=*/ None,
+        );
+        self.executor_thread_pool.install(|| {
+            txns.into_par_iter()
+                .map(|txn| vm.validate_transaction(txn, &self.data_store))
+                .collect()
+        })
+    }
+
     pub fn get_state_view(&self) -> &FakeDataStore {
         &self.data_store
     }
 
+    /// Starts a new block, advancing the block time by one microsecond.
+    ///
+    /// If the block time is frozen, or there are no validators to propose a block (e.g., after
+    /// [`FakeExecutor::framework_only_genesis`]), a NIL block is emitted at the current time
+    /// instead. A `NewBlockEvent` is emitted either way.
     pub fn new_block(&mut self) {
-        self.new_block_with_timestamp(self.block_time + 1);
+        let has_validators = ValidatorSet::fetch_config(&self.data_store.as_move_resolver())
+            .map_or(false, |validator_set| validator_set.num_validators() > 0);
+        if self.block_time_frozen || !has_validators {
+            // The block prologue only accepts an unchanged timestamp for NIL blocks, i.e. blocks
+            // proposed by the VM reserved address.
+            self.new_block_with_metadata(AccountAddress::ZERO, vec![]);
+        } else {
+            self.new_block_with_timestamp(self.block_time + 1);
+        }
+    }
+
+    /// Starts a new block and reconfigures the chain, returning the epoch it moved to.
+    ///
+    /// Fails instead of returning the unchanged epoch if no `NewEpochEvent` was emitted, e.g.,
+    /// because reconfiguration is disabled or the block time is frozen.
+    pub fn trigger_reconfiguration(&mut self) -> anyhow::Result<u64> {
+        self.new_block();
+        let (_, events) = self.exec_with_events("reconfiguration", "reconfigure", vec![], vec![]);
+        let event = events
+            .iter()
+            .find(|event| event.is_new_epoch_event())
+            .ok_or_else(|| anyhow::anyhow!("Reconfiguration did not emit a NewEpochEvent"))?;
+        let new_epoch = NewEpochEvent::try_from_bytes(event.event_data())?.epoch();
+
+        let configuration =
+            ConfigurationResource::fetch_config(&self.data_store.as_move_resolver())
+                .ok_or_else(|| anyhow::anyhow!("Configuration resource is missing"))?;
+        anyhow::ensure!(
+            configuration.epoch() == new_epoch,
+            "On-chain epoch {} does not match the NewEpochEvent epoch {}",
+            configuration.epoch(),
+            new_epoch
+        );
+        Ok(new_epoch)
+    }
+
+    /// Freezes (or unfreezes) the block time, so that subsequent calls to `new_block` emit
+    /// blocks at the current timestamp instead of advancing it.
+    pub fn freeze_block_time(&mut self, frozen: bool) {
+        self.block_time_frozen = frozen;
     }
 
     pub fn new_block_with_timestamp(&mut self, time_microseconds: u64) {
@@ -757,26 +3045,111 @@ impl FakeExecutor {
         self.new_block_with_metadata(proposer, vec![])
     }
 
+    /// Starts a new block at `timestamp` proposed by the first validator, passing `votes` (one
+    /// entry per validator in the current validator set) as the previous block's voting bitmap.
+    pub fn new_block_with_votes(&mut self, votes: Vec<bool>, timestamp: u64) {
+        let validator_set = ValidatorSet::fetch_config(&self.data_store.as_move_resolver())
+            .expect("Unable to retrieve the validator set from storage");
+        assert_eq!(
+            votes.len(),
+            validator_set.num_validators(),
+            "Voting bitmap length does not match the number of validators"
+        );
+        let mut bitvec = BitVec::with_num_bits(votes.len() as u16);
+        for (index, _) in votes.iter().enumerate().filter(|(_, voted)| **voted) {
+            bitvec.set(index as u16);
+        }
+
+        self.block_time = timestamp;
+        let proposer = *validator_set.payload().next().unwrap().account_address();
+        self.run_block_with_votes(proposer, bitvec.into(), vec![], vec![]);
+    }
+
     pub fn run_block_with_metadata(
         &mut self,
         proposer: AccountAddress,
         failed_proposer_indices: Vec<u32>,
         txns: Vec<SignedTransaction>,
+    ) -> Vec<(TransactionStatus, u64)> {
+        let previous_block_votes_bitvec = self.previous_block_votes_bitvec();
+        self.run_block_with_votes(
+            proposer,
+            previous_block_votes_bitvec,
+            failed_proposer_indices,
+            txns,
+        )
+    }
+
+    /// Runs a block like [`FakeExecutor::run_block_with_metadata`], passing
+    /// `previous_block_votes_bitvec` as the voting bitmap of the previous block.
+    ///
+    /// Panics if the bitmap does not hold exactly one bit per validator, rounded up to a whole
+    /// number of bytes.
+    pub fn run_block_with_votes(
+        &mut self,
+        proposer: AccountAddress,
+        previous_block_votes_bitvec: Vec<u8>,
+        failed_proposer_indices: Vec<u32>,
+        txns: Vec<SignedTransaction>,
     ) -> Vec<(TransactionStatus, u64)> {
         let mut txn_block: Vec<Transaction> =
             txns.into_iter().map(Transaction::UserTransaction).collect();
         let validator_set = ValidatorSet::fetch_config(&self.data_store.as_move_resolver())
             .expect("Unable to retrieve the validator set from storage");
-        let new_block_metadata = BlockMetadata::new(
-            HashValue::zero(),
-            0,
-            0,
+        // Check the metadata up front, the block prologue would only abort with an opaque status.
+        assert!(
+            proposer == AccountAddress::ZERO
+                || validator_set
+                    .payload()
+                    .any(|validator| validator.account_address() == &proposer),
+            "Proposer {} is neither the VM reserved address nor one of the {} validators",
             proposer,
-            BitVec::with_num_bits(validator_set.num_validators() as u16).into(),
-            failed_proposer_indices,
-            self.block_time,
+            validator_set.num_validators(),
         );
-        txn_block.insert(0, Transaction::BlockMetadata(new_block_metadata));
+        for index in &failed_proposer_indices {
+            assert!(
+                (*index as usize) < validator_set.num_validators(),
+                "Failed proposer index {} is out of range for {} validators",
+                index,
+                validator_set.num_validators(),
+            );
+        }
+        assert_eq!(
+            previous_block_votes_bitvec.len(),
+            (validator_set.num_validators() + 7) / 8,
+            "Voting bitmap of {} bytes does not match the {} validators",
+            previous_block_votes_bitvec.len(),
+            validator_set.num_validators(),
+        );
+        let expected_failed_proposer_indices: Vec<u64> = failed_proposer_indices
+            .iter()
+            .map(|index| *index as u64)
+            .collect();
+        let new_block_metadata = match &self.block_randomness_seed {
+            Some(seed) => Transaction::BlockMetadataExt(BlockMetadataExt::new_v1(
+                HashValue::zero(),
+                0,
+                0,
+                proposer,
+                previous_block_votes_bitvec,
+                failed_proposer_indices,
+                self.block_time,
+                Some(Randomness::new(
+                    RandMetadata { epoch: 0, round: 0 },
+                    seed.clone(),
+                )),
+            )),
+            None => Transaction::BlockMetadata(BlockMetadata::new(
+                HashValue::zero(),
+                0,
+                0,
+                proposer,
+                previous_block_votes_bitvec,
+                failed_proposer_indices,
+                self.block_time,
+            )),
+        };
+        txn_block.insert(0, new_block_metadata);
 
         let outputs = self
             .execute_transaction_block(txn_block)
@@ -789,6 +3162,11 @@ impl FakeExecutor {
             .clone();
         assert_eq!(event.key(), &new_block_event_key());
         assert!(bcs::from_bytes::<NewBlockEvent>(event.event_data()).is_ok());
+        self.assert_event_emitted::<NewBlockEvent>(&outputs[0], &new_block_event_key(), |event| {
+            event.proposer() == proposer
+                && event.proposed_time() == self.block_time
+                && event.failed_proposer_indices() == &expected_failed_proposer_indices
+        });
 
         let mut results = vec![];
         for output in outputs {
@@ -800,6 +3178,21 @@ impl FakeExecutor {
         results
     }
 
+    /// Returns the voting bitmap passed to the block prologue by
+    /// [`FakeExecutor::run_block_with_metadata`], i.e. one (unset) bit per validator in the
+    /// current validator set.
+    pub fn previous_block_votes_bitvec(&self) -> Vec<u8> {
+        let validator_set = ValidatorSet::fetch_config(&self.data_store.as_move_resolver())
+            .expect("Unable to retrieve the validator set from storage");
+        let num_validators = u16::try_from(validator_set.num_validators()).unwrap_or_else(|_| {
+            panic!(
+                "Voting bitmap cannot represent {} validators",
+                validator_set.num_validators()
+            )
+        });
+        BitVec::with_num_bits(num_validators).into()
+    }
+
     pub fn new_block_with_metadata(
         &mut self,
         proposer: AccountAddress,
@@ -808,8 +3201,8 @@ impl FakeExecutor {
         self.run_block_with_metadata(proposer, failed_proposer_indices, vec![]);
     }
 
-    fn module(name: &str) -> ModuleId {
-        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(name).unwrap())
+    fn module(&self, name: &str) -> ModuleId {
+        ModuleId::new(self.framework_address, Identifier::new(name).unwrap())
     }
 
     fn name(name: &str) -> Identifier {
@@ -820,6 +3213,33 @@ impl FakeExecutor {
         self.block_time = new_block_time;
     }
 
+    /// Overwrites the on-chain `CurrentTimeMicroseconds` resource and keeps the block time in
+    /// sync, so that time-gated Move code can be tested without running a block prologue.
+    pub fn set_onchain_timestamp(&mut self, micros: u64) {
+        let state_key = StateKey::on_chain_config::<CurrentTimeMicroseconds>()
+            .expect("failed to create StateKey");
+        let timestamp = CurrentTimeMicroseconds {
+            microseconds: micros,
+        };
+        self.write_state_value(state_key, bcs::to_bytes(&timestamp).unwrap());
+        self.block_time = micros;
+    }
+
+    /// Overwrites the seed of the on-chain `PerBlockRandomness` resource, so that Move code
+    /// reading randomness observes a controlled value. Blocks started afterwards, e.g. by
+    /// `new_block`, run the block prologue with the same seed instead of clearing it.
+    pub fn set_block_randomness_seed(&mut self, seed: Vec<u8>) {
+        let randomness = PerBlockRandomness {
+            seed: Some(seed.clone()),
+            ..PerBlockRandomness::fetch_config(&self.data_store.as_move_resolver())
+                .unwrap_or_default()
+        };
+        let state_key =
+            StateKey::on_chain_config::<PerBlockRandomness>().expect("failed to create StateKey");
+        self.write_state_value(state_key, bcs::to_bytes(&randomness).unwrap());
+        self.block_randomness_seed = Some(seed);
+    }
+
     pub fn get_block_time(&mut self) -> u64 {
         self.block_time
     }
@@ -841,6 +3261,9 @@ impl FakeExecutor {
         dynamic_args: ExecFuncTimerDynamicArgs,
         gas_meter_type: GasMeterType,
     ) -> u128 {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
         // FIXME: should probably read the timestamp from storage.
         let timed_features = TimedFeaturesBuilder::enable_all()
             .with_override_profile(TimedFeatureOverride::Testing)
@@ -972,6 +3395,9 @@ impl FakeExecutor {
         type_params: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
     ) -> Vec<DynamicExpression> {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
         // Define the shared buffers
         let a1 = Arc::new(Mutex::new(Vec::<DynamicExpression>::new()));
         let a2 = Arc::clone(&a1);
@@ -1055,6 +3481,24 @@ impl FakeExecutor {
         type_params: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
     ) {
+        self.exec_module_with_events(module_id, function_name, type_params, args);
+    }
+
+    /// Like [`FakeExecutor::exec_module`], but also returns the write set that was applied and
+    /// the events emitted by the call, in emission order.
+    pub fn exec_module_with_events(
+        &mut self,
+        module_id: &ModuleId,
+        function_name: &str,
+        type_params: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) -> (WriteSet, Vec<ContractEvent>) {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
+        let _capture = DebugOutputCapture::new(&self.debug_output);
+        #[cfg(feature = "testing")]
+        let _native_calls = NativeCallCapture::new(self.native_calls.as_ref());
         let (write_set, events) = {
             // FIXME: should probably read the timestamp from storage.
             let timed_features = TimedFeaturesBuilder::enable_all()
@@ -1087,12 +3531,7 @@ impl FakeExecutor {
                     &mut TraversalContext::new(&storage),
                 )
                 .unwrap_or_else(|e| {
-                    panic!(
-                        "Error calling {}.{}: {}",
-                        module_id,
-                        function_name,
-                        e.into_vm_status()
-                    )
+                    panic!("Error calling {}.{}: {:?}", module_id, function_name, e)
                 });
             let change_set = session
                 .finish(&ChangeSetConfigs::unlimited_at_gas_feature_version(
@@ -1105,7 +3544,62 @@ impl FakeExecutor {
                 .into_inner()
         };
         self.data_store.add_write_set(&write_set);
+        self.event_store.extend(events.iter().cloned());
+        (write_set, events)
+    }
+
+    /// Runs `f` against a fresh VM session, then finishes the session and applies its write set
+    /// (and events) to the data store.
+    ///
+    /// Unlike [`FakeExecutor::exec`], which makes a single call, this lets tests make several
+    /// calls (e.g., with `execute_function_bypass_visibility`) whose effects are combined into
+    /// one change set. Returns the value returned by `f` along with the applied write set.
+    pub fn with_session<R>(
+        &mut self,
+        f: impl FnOnce(&mut SessionExt<'_, '_>) -> R,
+    ) -> (R, WriteSet) {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
+        let _capture = DebugOutputCapture::new(&self.debug_output);
+        #[cfg(feature = "testing")]
+        let _native_calls = NativeCallCapture::new(self.native_calls.as_ref());
+        let (result, write_set, events) = {
+            // FIXME: should probably read the timestamp from storage.
+            let timed_features = TimedFeaturesBuilder::enable_all()
+                .with_override_profile(TimedFeatureOverride::Testing)
+                .build();
+
+            let resolver = self.data_store.as_move_resolver();
+
+            // TODO(Gas): we probably want to switch to non-zero costs in the future
+            let vm = MoveVmExt::new(
+                NativeGasParameters::zeros(),
+                MiscGasParameters::zeros(),
+                LATEST_GAS_FEATURE_VERSION,
+                self.chain_id,
+                self.features.clone(),
+                timed_features,
+                &resolver,
+                false,
+            )
+            .unwrap();
+            let mut session = vm.new_session(&resolver, SessionId::void(), None);
+            let result = f(&mut session);
+            let change_set = session
+                .finish(&ChangeSetConfigs::unlimited_at_gas_feature_version(
+                    LATEST_GAS_FEATURE_VERSION,
+                ))
+                .expect("Failed to generate txn effects");
+            let (write_set, events) = change_set
+                .try_into_storage_change_set()
+                .expect("Failed to convert to ChangeSet")
+                .into_inner();
+            (result, write_set, events)
+        };
+        self.data_store.add_write_set(&write_set);
         self.event_store.extend(events);
+        (result, write_set)
     }
 
     pub fn exec(
@@ -1115,7 +3609,19 @@ impl FakeExecutor {
         type_params: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
     ) {
-        self.exec_module(&Self::module(module_name), function_name, type_params, args)
+        self.exec_module(&self.module(module_name), function_name, type_params, args)
+    }
+
+    /// Like [`FakeExecutor::exec`], but also returns the write set that was applied and the
+    /// events emitted by the call, in emission order.
+    pub fn exec_with_events(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        type_params: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) -> (WriteSet, Vec<ContractEvent>) {
+        self.exec_module_with_events(&self.module(module_name), function_name, type_params, args)
     }
 
     pub fn try_exec_entry_with_state_view(
@@ -1125,6 +3631,9 @@ impl FakeExecutor {
         state_view: &impl AptosMoveResolver,
         features: Features,
     ) -> Result<(WriteSet, Vec<ContractEvent>), VMStatus> {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
         let (
             gas_params_res,
             storage_gas_params,
@@ -1192,13 +3701,62 @@ impl FakeExecutor {
         Ok((write_set, events))
     }
 
+    /// Like [`FakeExecutor::exec`], but returns the effects of the call instead of applying them.
+    ///
+    /// Failures are reported as the full [`VMError`], whose sub-status, message, location and
+    /// indices often carry the actual reason a native function failed.
     pub fn try_exec(
         &mut self,
         module_name: &str,
         function_name: &str,
         type_params: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
-    ) -> Result<(WriteSet, Vec<ContractEvent>), VMStatus> {
+    ) -> Result<(WriteSet, Vec<ContractEvent>), VMError> {
+        self.try_exec_with_gas_meter(
+            module_name,
+            function_name,
+            type_params,
+            args,
+            &mut UnmeteredGasMeter,
+        )
+    }
+
+    /// Like [`Self::try_exec`], but fails once the execution exceeds the given call depth or
+    /// number of steps, with the status code documented on [`VmLimits`].
+    ///
+    /// This is meant for testing how code behaves when deeply recursive or long running, without
+    /// having to actually hit the (much larger) limits of the VM.
+    pub fn try_exec_with_limits(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        type_params: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+        limits: VmLimits,
+    ) -> Result<(WriteSet, Vec<ContractEvent>), VMError> {
+        self.try_exec_with_gas_meter(
+            module_name,
+            function_name,
+            type_params,
+            args,
+            &mut LimitedGasMeter::new(limits),
+        )
+    }
+
+    fn try_exec_with_gas_meter(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        type_params: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+        gas_meter: &mut impl GasMeter,
+    ) -> Result<(WriteSet, Vec<ContractEvent>), VMError> {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
+        let _capture = DebugOutputCapture::new(&self.debug_output);
+        #[cfg(feature = "testing")]
+        let _native_calls = NativeCallCapture::new(self.native_calls.as_ref());
         let resolver = self.data_store.as_move_resolver();
 
         // TODO(Gas): we probably want to switch to non-zero costs in the future
@@ -1216,16 +3774,14 @@ impl FakeExecutor {
         .unwrap();
         let mut session = vm.new_session(&resolver, SessionId::void(), None);
         let storage = TraversalStorage::new();
-        session
-            .execute_function_bypass_visibility(
-                &Self::module(module_name),
-                &Self::name(function_name),
-                type_params,
-                args,
-                &mut UnmeteredGasMeter,
-                &mut TraversalContext::new(&storage),
-            )
-            .map_err(|e| e.into_vm_status())?;
+        session.execute_function_bypass_visibility(
+            &self.module(module_name),
+            &Self::name(function_name),
+            type_params,
+            args,
+            gas_meter,
+            &mut TraversalContext::new(&storage),
+        )?;
 
         let change_set = session
             .finish(&ChangeSetConfigs::unlimited_at_gas_feature_version(
@@ -1240,12 +3796,118 @@ impl FakeExecutor {
         Ok((write_set, events))
     }
 
+    /// Runs the transaction epilogue for `sender` with explicit gas values, independently of
+    /// any actual execution, and returns its effects without applying them.
+    ///
+    /// This is meant for testing fee computation at its boundaries. For example, a
+    /// `gas_unit_price * (max_gas_units - gas_units_remaining)` product that does not fit into a
+    /// `u64` makes the epilogue abort with `EOUT_OF_GAS` (in the out of range category), which
+    /// is surfaced as a [`VMStatus::MoveAbort`].
+    pub fn run_epilogue(
+        &mut self,
+        sender: AccountAddress,
+        max_gas_units: u64,
+        gas_units_remaining: u64,
+        gas_unit_price: u64,
+    ) -> Result<(WriteSet, Vec<ContractEvent>), VMStatus> {
+        let storage_fee_refunded = 0u64;
+        self.try_exec(
+            "transaction_validation",
+            "epilogue",
+            vec![],
+            vec![
+                bcs::to_bytes(&sender).unwrap(),
+                bcs::to_bytes(&storage_fee_refunded).unwrap(),
+                bcs::to_bytes(&gas_unit_price).unwrap(),
+                bcs::to_bytes(&max_gas_units).unwrap(),
+                bcs::to_bytes(&gas_units_remaining).unwrap(),
+            ],
+        )
+        .map_err(VMError::into_vm_status)
+    }
+
+    /// Executes a transaction script directly through a session with the given signers and
+    /// applies the resulting write set (and events).
+    ///
+    /// Scripts that fail bytecode verification (or abort) are reported as a [`VMStatus`] and
+    /// leave the data store untouched.
+    pub fn exec_script(
+        &mut self,
+        script_bytes: Vec<u8>,
+        type_params: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+        signers: Vec<AccountAddress>,
+    ) -> Result<(WriteSet, Vec<ContractEvent>), VMStatus> {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
+        let _capture = DebugOutputCapture::new(&self.debug_output);
+        #[cfg(feature = "testing")]
+        let _native_calls = NativeCallCapture::new(self.native_calls.as_ref());
+        let (write_set, events) = {
+            // FIXME: should probably read the timestamp from storage.
+            let timed_features = TimedFeaturesBuilder::enable_all()
+                .with_override_profile(TimedFeatureOverride::Testing)
+                .build();
+
+            let resolver = self.data_store.as_move_resolver();
+
+            // TODO(Gas): we probably want to switch to non-zero costs in the future
+            let vm = MoveVmExt::new(
+                NativeGasParameters::zeros(),
+                MiscGasParameters::zeros(),
+                LATEST_GAS_FEATURE_VERSION,
+                self.chain_id,
+                self.features.clone(),
+                timed_features,
+                &resolver,
+                false,
+            )
+            .unwrap();
+            let mut session = vm.new_session(&resolver, SessionId::void(), None);
+
+            // Signers are passed to the script as their serialized addresses, ahead of the
+            // regular arguments.
+            let args = signers
+                .iter()
+                .map(|signer| bcs::to_bytes(signer).unwrap())
+                .chain(args)
+                .collect::<Vec<_>>();
+            let storage = TraversalStorage::new();
+            session
+                .execute_script(
+                    script_bytes,
+                    type_params,
+                    args,
+                    &mut UnmeteredGasMeter,
+                    &mut TraversalContext::new(&storage),
+                )
+                .map_err(|e| e.into_vm_status())?;
+
+            let change_set = session
+                .finish(&ChangeSetConfigs::unlimited_at_gas_feature_version(
+                    LATEST_GAS_FEATURE_VERSION,
+                ))
+                .map_err(|e| e.into_vm_status())?;
+            change_set
+                .try_into_storage_change_set()
+                .expect("Failed to convert to ChangeSet")
+                .into_inner()
+        };
+        self.data_store.add_write_set(&write_set);
+        self.event_store.extend(events.iter().cloned());
+        Ok((write_set, events))
+    }
+
     pub fn execute_view_function(
         &mut self,
         fun: MemberId,
         type_args: Vec<TypeTag>,
         arguments: Vec<Vec<u8>>,
     ) -> ViewFunctionOutput {
+        let _module_cache = self.module_cache.enter();
+        #[cfg(feature = "testing")]
+        let _natives = NativesAddressScope::new(self.framework_address);
         // No gas limit
         AptosVM::execute_view_function(
             self.get_state_view(),
@@ -4,14 +4,20 @@
 
 use goldenfile::Mint;
 use move_command_line_common::testing::EXP_EXT;
-use std::{cell::RefCell, fmt::Debug, fs::File, io::Write, path::PathBuf};
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
 
 pub const GOLDEN_DIR_PATH: &str = "goldens";
 
 pub(crate) struct GoldenOutputs {
     #[allow(dead_code)]
     mint: Mint,
-    file: RefCell<File>,
+    file: Mutex<BufWriter<File>>,
 }
 
 fn golden_path() -> PathBuf {
@@ -29,15 +35,35 @@ impl GoldenOutputs {
         let mut mint = Mint::new(path);
         let mut file_path = PathBuf::new();
         file_path.push(name);
-        let file = RefCell::new(
+        let file = Mutex::new(BufWriter::new(
             mint.new_goldenfile(file_path.with_extension(EXP_EXT))
                 .unwrap(),
-        );
+        ));
         Self { mint, file }
     }
 
     pub fn log(&self, msg: &str) {
-        self.file.borrow_mut().write_all(msg.as_bytes()).unwrap();
+        self.file.lock().unwrap().write_all(msg.as_bytes()).unwrap();
+    }
+
+    /// Like `log`, but terminates the message with a newline.
+    pub fn log_line(&self, msg: &str) {
+        let mut file = self.file.lock().unwrap();
+        file.write_all(msg.as_bytes()).unwrap();
+        file.write_all(b"\n").unwrap();
+    }
+}
+
+impl Drop for GoldenOutputs {
+    fn drop(&mut self) {
+        // The mint compares the golden files when it is dropped, which happens right after this,
+        // so make sure everything buffered so far has hit the file. This also keeps the file
+        // complete when a test panics midway.
+        let file = self
+            .file
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = file.flush();
     }
 }
 
@@ -8,10 +8,14 @@
 //!
 //! This crate contains helpers for executing tests against the Aptos VM.
 
-use aptos_types::{transaction::TransactionStatus, vm_status::KeptVMStatus};
+use aptos_types::{
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::KeptVMStatus,
+};
 
 pub mod account;
 pub mod account_universe;
+pub mod arguments;
 pub mod common_transactions;
 pub mod compile;
 pub mod data_store;
@@ -19,6 +23,7 @@ pub mod execution_strategies;
 pub mod executor;
 pub mod gas_costs;
 mod golden_outputs;
+pub mod limits;
 pub mod loader;
 mod proptest_types;
 
@@ -41,6 +46,21 @@ pub fn transaction_status_eq(t1: &TransactionStatus, t2: &TransactionStatus) ->
     }
 }
 
+/// Returns the number of bytes written by the output, counting both the state keys and the
+/// values they are set to (deletions only count the key), as storage fees do.
+pub fn write_set_byte_size(output: &TransactionOutput) -> usize {
+    output
+        .write_set()
+        .iter()
+        .map(|(state_key, write_op)| state_key.size() + write_op.size())
+        .sum()
+}
+
+/// Returns the number of distinct state keys written (or deleted) by the output.
+pub fn num_state_writes(output: &TransactionOutput) -> usize {
+    output.write_set().iter().count()
+}
+
 #[macro_export]
 macro_rules! assert_prologue_parity {
     ($e1:expr, $e2:expr, $e3:expr) => {
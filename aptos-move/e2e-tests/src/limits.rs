@@ -0,0 +1,343 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Artificial execution limits for functions run through
+//! [`FakeExecutor::try_exec_with_limits`][crate::executor::FakeExecutor::try_exec_with_limits].
+
+use move_binary_format::{
+    errors::{PartialVMError, PartialVMResult},
+    file_format::CodeOffset,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, NumArgs, NumBytes, NumTypeNodes},
+    identifier::IdentStr,
+    language_storage::ModuleId,
+    vm_status::StatusCode,
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+
+/// Limits on the execution of a single Move function.
+///
+/// Exceeding `max_call_depth` fails with [`StatusCode::CALL_STACK_OVERFLOW`], and exceeding
+/// `max_steps` fails with [`StatusCode::EXECUTION_LIMIT_REACHED`]. A limit set to `None` is not
+/// enforced.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VmLimits {
+    /// The maximum number of nested Move or native function frames, counting the called
+    /// function itself.
+    pub max_call_depth: Option<usize>,
+    /// The maximum number of bytecode instructions executed.
+    pub max_steps: Option<u64>,
+}
+
+impl VmLimits {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = Some(max_call_depth);
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+}
+
+/// A gas meter that charges nothing, but enforces [`VmLimits`] by counting the instructions and
+/// frames reported to it by the interpreter.
+pub(crate) struct LimitedGasMeter {
+    limits: VmLimits,
+    steps: u64,
+    depth: usize,
+}
+
+impl LimitedGasMeter {
+    pub(crate) fn new(limits: VmLimits) -> Self {
+        Self {
+            limits,
+            steps: 0,
+            // The called function is not charged for, so it is accounted for from the start.
+            depth: 1,
+        }
+    }
+
+    fn step(&mut self) -> PartialVMResult<()> {
+        self.steps += 1;
+        match self.limits.max_steps {
+            Some(max_steps) if self.steps > max_steps => {
+                Err(PartialVMError::new(StatusCode::EXECUTION_LIMIT_REACHED)
+                    .with_message(format!("Exceeded the limit of {} steps", max_steps)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn enter_frame(&mut self) -> PartialVMResult<()> {
+        self.step()?;
+        self.depth += 1;
+        match self.limits.max_call_depth {
+            Some(max_call_depth) if self.depth > max_call_depth => Err(PartialVMError::new(
+                StatusCode::CALL_STACK_OVERFLOW,
+            )
+            .with_message(format!(
+                "Exceeded the call depth limit of {}",
+                max_call_depth
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn exit_frame(&mut self) -> PartialVMResult<()> {
+        self.depth = self.depth.saturating_sub(1);
+        Ok(())
+    }
+}
+
+impl GasMeter for LimitedGasMeter {
+    fn balance_internal(&self) -> InternalGas {
+        u64::MAX.into()
+    }
+
+    fn charge_simple_instr(&mut self, _instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_br_false(&mut self, _target_offset: Option<CodeOffset>) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_br_true(&mut self, _target_offset: Option<CodeOffset>) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_branch(&mut self, _target_offset: CodeOffset) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_pop(&mut self, _popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_call(
+        &mut self,
+        _module_id: &ModuleId,
+        _func_name: &str,
+        _args: impl IntoIterator<Item = impl ValueView>,
+        _num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.enter_frame()
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        _module_id: &ModuleId,
+        _func_name: &str,
+        _ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        _args: impl ExactSizeIterator<Item = impl ValueView>,
+        _num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.enter_frame()
+    }
+
+    fn charge_ld_const(&mut self, _size: NumBytes) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        _val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    fn charge_copy_loc(&mut self, _val: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_move_loc(&mut self, _val: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_store_loc(&mut self, _val: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_pack(
+        &mut self,
+        _is_generic: bool,
+        _args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_unpack(
+        &mut self,
+        _is_generic: bool,
+        _args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_read_ref(&mut self, _val: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        _new_val: impl ValueView,
+        _old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_eq(&mut self, _lhs: impl ValueView, _rhs: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_neq(&mut self, _lhs: impl ValueView, _rhs: impl ValueView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        _is_mut: bool,
+        _is_generic: bool,
+        _ty: impl TypeView,
+        _is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_exists(
+        &mut self,
+        _is_generic: bool,
+        _ty: impl TypeView,
+        _exists: bool,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_move_from(
+        &mut self,
+        _is_generic: bool,
+        _ty: impl TypeView,
+        _val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_move_to(
+        &mut self,
+        _is_generic: bool,
+        _ty: impl TypeView,
+        _val: impl ValueView,
+        _is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_pack<'a>(
+        &mut self,
+        _ty: impl TypeView + 'a,
+        _args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_len(&mut self, _ty: impl TypeView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        _is_mut: bool,
+        _ty: impl TypeView,
+        _is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        _ty: impl TypeView,
+        _val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        _ty: impl TypeView,
+        _val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        _ty: impl TypeView,
+        _expect_num_elements: NumArgs,
+        _elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_vec_swap(&mut self, _ty: impl TypeView) -> PartialVMResult<()> {
+        self.step()
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        _addr: AccountAddress,
+        _ty: impl TypeView,
+        _val: Option<impl ValueView>,
+        _bytes_loaded: NumBytes,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    fn charge_native_function(
+        &mut self,
+        _amount: InternalGas,
+        _ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        // Natives run without a frame of their own, so they return as soon as they are charged.
+        self.exit_frame()
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        _ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        _args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        _locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.exit_frame()
+    }
+
+    fn charge_create_ty(&mut self, _num_nodes: NumTypeNodes) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    fn charge_dependency(
+        &mut self,
+        _is_new: bool,
+        _addr: &AccountAddress,
+        _name: &IdentStr,
+        _size: NumBytes,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_language_e2e_tests::executor::FakeExecutor;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{new_block_event_key, NewBlockEvent},
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use std::panic::{self, AssertUnwindSafe};
+
+fn validator_set(executor: &FakeExecutor) -> ValidatorSet {
+    ValidatorSet::fetch_config(&executor.get_state_view().as_move_resolver()).unwrap()
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let err = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    err.downcast_ref::<String>()
+        .cloned()
+        .expect("Panic message must be formatted")
+}
+
+#[test]
+fn block_metadata_is_validated_up_front() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let validator_set = validator_set(&executor);
+    let num_validators = validator_set.num_validators();
+    let proposer = *validator_set.payload().next().unwrap().account_address();
+
+    let bitvec = executor.previous_block_votes_bitvec();
+    assert_eq!(bitvec.len(), (num_validators + 7) / 8);
+    assert!(bitvec.iter().all(|byte| *byte == 0));
+
+    let mut malformed_bitvec = bitvec.clone();
+    malformed_bitvec.push(0);
+    let message = panic_message(|| {
+        executor.run_block_with_votes(proposer, malformed_bitvec, vec![], vec![]);
+    });
+    assert!(message.contains(&format!(
+        "Voting bitmap of {} bytes does not match the {} validators",
+        bitvec.len() + 1,
+        num_validators
+    )));
+
+    let message = panic_message(|| {
+        executor.run_block_with_metadata(AccountAddress::random(), vec![], vec![]);
+    });
+    assert!(message.contains("is neither the VM reserved address nor one of the"));
+
+    let message = panic_message(|| {
+        executor.run_block_with_metadata(proposer, vec![num_validators as u32], vec![]);
+    });
+    assert_eq!(
+        message,
+        format!(
+            "Failed proposer index {} is out of range for {} validators",
+            num_validators, num_validators
+        )
+    );
+
+    // Nothing was executed by the rejected blocks, so a well-formed block still goes through.
+    let results = executor.run_block_with_votes(proposer, bitvec, vec![], vec![]);
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0].0,
+        TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
+
+#[test]
+fn new_block_with_votes_passes_the_voting_bitmap() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let num_validators = validator_set(&executor).num_validators();
+    let timestamp = executor.get_block_time() + 1;
+
+    let message = panic_message(|| {
+        executor.new_block_with_votes(vec![true; num_validators + 1], timestamp);
+    });
+    assert!(message.contains("Voting bitmap length does not match the number of validators"));
+
+    // Only the first validator voted.
+    executor.enable_memory_trace();
+    executor.new_block_with_votes(
+        (0..num_validators).map(|index| index == 0).collect(),
+        timestamp,
+    );
+    assert_eq!(executor.get_block_time(), timestamp);
+
+    let trace = executor.take_trace();
+    let output = trace[0].output.as_ref().unwrap();
+    let event = output
+        .events()
+        .iter()
+        .filter(|event| event.event_key() == Some(&new_block_event_key()))
+        .map(|event| bcs::from_bytes::<NewBlockEvent>(event.event_data()).unwrap())
+        .next()
+        .unwrap();
+    let mut expected_bitvec = vec![0; (num_validators + 7) / 8];
+    expected_bitvec[0] = 0b1000_0000;
+    assert_eq!(event.previous_block_votes_bitvec(), &expected_bitvec);
+}
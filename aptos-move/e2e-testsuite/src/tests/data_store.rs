@@ -3,16 +3,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_language_e2e_tests::{
-    account::AccountData, compile::compile_script, current_function_name,
-    data_store::FakeDataStore, executor::FakeExecutor,
+    account::AccountData,
+    common_transactions::peer_to_peer_txn,
+    compile::compile_script,
+    current_function_name,
+    data_store::{FakeDataStore, GENESIS_CHANGE_SET_HEAD},
+    executor::{CompatibilityError, FakeExecutor, StateDiff},
 };
-use aptos_types::transaction::{
-    ExecutionStatus, SignedTransaction, Transaction, TransactionStatus,
+use aptos_types::{
+    account_config::{AccountResource, CoinStoreResource, CORE_CODE_ADDRESS},
+    chain_id::ChainId,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{
+        ExecutionStatus, SignedTransaction, Transaction, TransactionAuxiliaryData,
+        TransactionOutput, TransactionStatus,
+    },
+    write_set::{WriteOp, WriteSetMut},
 };
 use claims::assert_matches;
 use move_binary_format::CompiledModule;
 use move_bytecode_verifier::verify_module;
+use move_core_types::{
+    account_address::AccountAddress, ident_str, identifier::Identifier, language_storage::ModuleId,
+    move_resource::MoveStructType,
+};
 use move_ir_compiler::Compiler;
+use move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage};
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::panic::{self, AssertUnwindSafe};
 
 #[test]
 fn move_from_across_blocks() {
@@ -202,6 +220,344 @@ fn change_after_move() {
     executor.apply_write_set(output.write_set());
 }
 
+#[test]
+fn diff_between_executors() {
+    let mut before = FakeExecutor::no_genesis();
+    let mut after = FakeExecutor::no_genesis();
+    for executor in [&mut before, &mut after] {
+        executor.write_state_value(StateKey::raw(b"unchanged"), vec![0]);
+    }
+    assert!(before.diff(&after).is_empty());
+
+    before.write_state_value(StateKey::raw(b"modified"), vec![1]);
+    after.write_state_value(StateKey::raw(b"modified"), vec![2]);
+    before.write_state_value(StateKey::raw(b"removed"), vec![3]);
+    after.write_state_value(StateKey::raw(b"added"), vec![4]);
+    let expected = vec![
+        (
+            StateKey::raw(b"added"),
+            StateDiff::Added(StateValue::from(vec![4])),
+        ),
+        (
+            StateKey::raw(b"modified"),
+            StateDiff::Modified {
+                before: StateValue::from(vec![1]),
+                after: StateValue::from(vec![2]),
+            },
+        ),
+        (
+            StateKey::raw(b"removed"),
+            StateDiff::Removed(StateValue::from(vec![3])),
+        ),
+    ];
+    assert_eq!(before.diff(&after), expected);
+}
+
+#[test]
+fn list_resources_of_new_account() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let resources = executor.list_resources(sender.address());
+    let tags: Vec<_> = resources.iter().map(|(tag, _)| tag.clone()).collect();
+    let mut expected = vec![
+        AccountResource::struct_tag(),
+        CoinStoreResource::struct_tag(),
+    ];
+    expected.sort();
+    assert_eq!(tags, expected);
+    for (tag, bytes) in resources {
+        let state_key = StateKey::resource(sender.address(), &tag).unwrap();
+        assert_eq!(
+            executor
+                .read_state_value_bytes(&state_key)
+                .map(|bytes| bytes.to_vec()),
+            Some(bytes)
+        );
+    }
+    assert!(executor
+        .list_resources(&AccountAddress::random())
+        .is_empty());
+}
+
+#[test]
+fn head_genesis_stores_are_independent() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let other = FakeExecutor::from_head_genesis();
+    let account = executor.new_funded_account(1_000);
+
+    assert!(executor.read_account_resource(&account).is_some());
+    assert!(other.read_account_resource(&account).is_none());
+    assert!(FakeExecutor::from_head_genesis()
+        .read_account_resource(&account)
+        .is_none());
+}
+
+#[test]
+fn cached_genesis_matches_applied_genesis() {
+    let genesis_write_set = GENESIS_CHANGE_SET_HEAD.write_set();
+    let applied = FakeExecutor::from_genesis(genesis_write_set, ChainId::test());
+    for cached in [
+        FakeExecutor::from_head_genesis(),
+        FakeExecutor::from_genesis_file(),
+    ] {
+        assert_eq!(cached.last_applied_write_set(), Some(genesis_write_set));
+        assert_eq!(
+            cached.last_applied_write_set(),
+            applied.last_applied_write_set()
+        );
+        assert!(cached.diff(&applied).is_empty());
+    }
+}
+
+#[test]
+fn overlay_commit_and_discard() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let account = executor.new_funded_account(1_000);
+
+    let handle = executor.begin_overlay();
+    let speculative = executor.new_funded_account(1_000);
+    assert!(executor.read_account_resource(&account).is_some());
+    assert!(executor.read_account_resource(&speculative).is_some());
+    executor.discard_overlay(handle);
+    assert!(executor.read_account_resource(&speculative).is_none());
+
+    let handle = executor.begin_overlay();
+    let committed = executor.new_funded_account(1_000);
+    executor.commit_overlay(handle);
+    assert!(executor.read_account_resource(&account).is_some());
+    assert!(executor.read_account_resource(&committed).is_some());
+}
+
+#[test]
+fn module_upgrade_compatibility() {
+    let compile = |code: &str| {
+        let module = Compiler { deps: vec![] }
+            .into_compiled_module(code)
+            .expect("Module compilation failed");
+        let mut module_bytes = vec![];
+        module
+            .serialize(&mut module_bytes)
+            .expect("Module must serialize");
+        module_bytes
+    };
+    let old = compile(
+        "
+        module 0x42.M {
+            struct T has key { v: u64 }
+            public f() { label b0: return; }
+        }
+        ",
+    );
+    let with_new_function = compile(
+        "
+        module 0x42.M {
+            struct T has key { v: u64 }
+            public f() { label b0: return; }
+            public g() { label b0: return; }
+        }
+        ",
+    );
+    let with_new_layout = compile(
+        "
+        module 0x42.M {
+            struct T has key { v: u64, w: u64 }
+            public f() { label b0: return; }
+        }
+        ",
+    );
+
+    assert!(FakeExecutor::check_upgrade_compatibility(&old, &with_new_function).is_ok());
+    assert!(matches!(
+        FakeExecutor::check_upgrade_compatibility(&old, &with_new_layout),
+        Err(CompatibilityError::Incompatible(_))
+    ));
+    assert!(matches!(
+        FakeExecutor::check_upgrade_compatibility(&old, &[]),
+        Err(CompatibilityError::InvalidModule(_))
+    ));
+}
+
+#[test]
+fn list_modules_under_address() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    executor.add_account_data(&sender);
+    assert!(executor.list_modules(sender.address()).is_empty());
+
+    let module = add_module(executor.data_store_mut(), &sender);
+    assert_eq!(
+        executor.list_modules(sender.address()),
+        vec![module.self_id()]
+    );
+    assert!(executor
+        .list_modules(&CORE_CODE_ADDRESS)
+        .iter()
+        .any(|module_id| module_id.name().as_str() == "coin"));
+}
+
+#[test]
+fn state_checksum_is_order_independent() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(4, 1_000_000, 0);
+    let txns = vec![
+        peer_to_peer_txn(&accounts[0], &accounts[1], 0, 1_000, 0),
+        peer_to_peer_txn(&accounts[2], &accounts[3], 0, 2_000, 0),
+    ];
+    let initial_checksum = executor.state_checksum();
+    assert_eq!(executor.state_checksum(), initial_checksum);
+
+    let mut checksum_after = |txns: Vec<SignedTransaction>| {
+        let handle = executor.begin_overlay();
+        for txn in txns {
+            executor.execute_and_apply(txn);
+        }
+        let checksum = executor.state_checksum();
+        executor.discard_overlay(handle);
+        checksum
+    };
+    let in_order = checksum_after(txns.clone());
+    let reversed = checksum_after(txns.into_iter().rev().collect());
+    assert_eq!(in_order, reversed);
+    assert_ne!(in_order, initial_checksum);
+}
+
+#[test]
+fn store_snapshot_matches_golden() {
+    let mut executor = FakeExecutor::no_genesis();
+    executor.write_state_value(StateKey::raw(b"b"), vec![4]);
+    executor.write_state_value(StateKey::raw(b"a"), vec![1, 2, 3]);
+
+    let entries = executor.data_store_entries();
+    assert_eq!(
+        entries.keys().cloned().collect::<Vec<_>>(),
+        vec![StateKey::raw(b"a"), StateKey::raw(b"b")]
+    );
+    executor.assert_store_matches_golden(current_function_name!());
+}
+
+/// Returns `0x42::M`, whose function `f` aborts with `abort_code`.
+fn module_aborting_with(abort_code: u64) -> CompiledModule {
+    let code = format!(
+        "module 0x42.M {{ public f() {{ label b0: abort {}; }} }}",
+        abort_code
+    );
+    Compiler { deps: vec![] }
+        .into_compiled_module(&code)
+        .expect("Module compilation failed")
+}
+
+fn serialize_module(module: &CompiledModule) -> Vec<u8> {
+    let mut module_bytes = vec![];
+    module
+        .serialize(&mut module_bytes)
+        .expect("Module must serialize");
+    module_bytes
+}
+
+/// Calls `0x42::M::f` and returns the code it aborted with.
+fn abort_code_of_f(executor: &mut FakeExecutor) -> Option<u64> {
+    let (result, _) = executor.with_session(|session| {
+        let storage = TraversalStorage::new();
+        session.execute_function_bypass_visibility(
+            &ModuleId::new(
+                AccountAddress::from_hex_literal("0x42").unwrap(),
+                Identifier::new("M").unwrap(),
+            ),
+            ident_str!("f"),
+            vec![],
+            Vec::<Vec<u8>>::new(),
+            &mut UnmeteredGasMeter,
+            &mut TraversalContext::new(&storage),
+        )
+    });
+    result.unwrap_err().sub_status()
+}
+
+#[test]
+fn overwritten_module_is_reloaded() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let add_module_aborting_with = |executor: &mut FakeExecutor, abort_code| {
+        let module = module_aborting_with(abort_code);
+        executor.add_module(&module.self_id(), serialize_module(&module));
+    };
+
+    add_module_aborting_with(&mut executor, 1);
+    assert_eq!(abort_code_of_f(&mut executor), Some(1));
+    // Without invalidating the module cache, the first version of the module would still run.
+    add_module_aborting_with(&mut executor, 2);
+    assert_eq!(abort_code_of_f(&mut executor), Some(2));
+}
+
+#[test]
+fn published_module_bundle_is_reloaded() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = AccountAddress::from_hex_literal("0x42").unwrap();
+
+    executor
+        .publish_module_bundle(sender, vec![serialize_module(&module_aborting_with(1))])
+        .unwrap();
+    assert_eq!(abort_code_of_f(&mut executor), Some(1));
+    // Changing the body of a function is a compatible upgrade.
+    executor
+        .publish_module_bundle(sender, vec![serialize_module(&module_aborting_with(2))])
+        .unwrap();
+    assert_eq!(abort_code_of_f(&mut executor), Some(2));
+}
+
+#[test]
+fn module_cache_is_per_executor() {
+    let module = module_aborting_with(1);
+    let mut first = FakeExecutor::from_head_genesis();
+    first.add_module(&module.self_id(), serialize_module(&module));
+    assert_eq!(abort_code_of_f(&mut first), Some(1));
+
+    // The same module, with the same configuration, is not shared with another executor.
+    let module = module_aborting_with(2);
+    let mut second = FakeExecutor::from_head_genesis();
+    second.add_module(&module.self_id(), serialize_module(&module));
+    assert_eq!(abort_code_of_f(&mut second), Some(2));
+    assert_eq!(abort_code_of_f(&mut first), Some(1));
+}
+
+#[test]
+fn module_writes_are_detected() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+    let output = executor.execute_transaction(txn);
+    executor.assert_no_module_writes(&output);
+
+    let module_id = ModuleId::new(
+        AccountAddress::from_hex_literal("0x42").unwrap(),
+        Identifier::new("M").unwrap(),
+    );
+    let write_set = WriteSetMut::new(vec![(
+        StateKey::module_id(&module_id),
+        WriteOp::legacy_creation(vec![].into()),
+    )])
+    .freeze()
+    .unwrap();
+    let output = TransactionOutput::new(
+        write_set,
+        vec![],
+        0,
+        TransactionStatus::Keep(ExecutionStatus::Success),
+        TransactionAuxiliaryData::default(),
+    );
+    let err = panic::catch_unwind(AssertUnwindSafe(|| {
+        executor.assert_no_module_writes(&output)
+    }))
+    .unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.ends_with(&module_id.to_string()));
+}
+
 fn add_module(data_store: &mut FakeDataStore, sender: &AccountData) -> CompiledModule {
     let code = format!(
         "
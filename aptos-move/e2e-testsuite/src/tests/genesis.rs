@@ -3,14 +3,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_language_e2e_tests::{
-    common_transactions::peer_to_peer_txn, data_store::GENESIS_CHANGE_SET_HEAD,
-    executor::FakeExecutor,
+    common_transactions::peer_to_peer_txn,
+    data_store::GENESIS_CHANGE_SET_HEAD,
+    executor::{FakeExecutor, GenesisError},
 };
 use aptos_types::{
-    transaction::{ChangeSet, Transaction, TransactionStatus, WriteSetPayload},
-    write_set::TransactionWrite,
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    state_store::state_key::StateKey,
+    transaction::{ChangeSet, ExecutionStatus, Transaction, TransactionStatus, WriteSetPayload},
+    write_set::{TransactionWrite, WriteOp, WriteSetMut},
 };
-use move_core_types::vm_status::StatusCode;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, vm_status::StatusCode};
 
 #[test]
 fn no_deletion_in_genesis() {
@@ -64,3 +68,113 @@ fn fail_no_epoch_change_write_set() {
         .unwrap_err();
     assert_eq!(StatusCode::INVALID_WRITE_SET, output_err.status_code());
 }
+
+#[test]
+fn framework_only_genesis_emits_nil_blocks() {
+    let mut executor = FakeExecutor::framework_only_genesis();
+    executor.validate_genesis().unwrap();
+    // Asserts that the block prologue emitted a `NewBlockEvent`.
+    executor.new_block();
+    assert_eq!(executor.get_block_time(), 0);
+}
+
+#[test]
+fn genesis_write_set_skips_parallel_comparison() {
+    let mut executor = FakeExecutor::no_genesis().set_parallel();
+    let genesis =
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(GENESIS_CHANGE_SET_HEAD.clone()));
+    let output = executor.execute_transaction_block(vec![genesis]).unwrap();
+    assert!(executor.parallel_comparison_skipped());
+    executor.apply_write_set(output[0].write_set());
+
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1000, 0);
+    executor
+        .execute_transaction_block(vec![Transaction::UserTransaction(txn)])
+        .unwrap();
+    assert!(!executor.parallel_comparison_skipped());
+}
+
+#[test]
+fn validate_genesis_reports_missing_state() {
+    FakeExecutor::from_head_genesis()
+        .validate_genesis()
+        .unwrap();
+    let framework = aptos_cached_packages::head_release_bundle();
+    assert!(FakeExecutor::try_custom_genesis(framework, None).is_ok());
+
+    let account_module = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("account").unwrap());
+    assert_eq!(
+        FakeExecutor::no_genesis().validate_genesis(),
+        Err(GenesisError::MissingModule(account_module))
+    );
+
+    // The framework is published, but nothing was initialized.
+    let framework_address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let executor = FakeExecutor::custom_genesis_at_address(framework_address, framework);
+    assert_eq!(
+        executor.validate_genesis(),
+        Err(GenesisError::MissingValidatorSet)
+    );
+}
+
+#[test]
+fn execute_writeset_applies_write_set() {
+    let mut executor = FakeExecutor::no_genesis();
+    assert!(executor.validate_genesis().is_err());
+
+    let output =
+        executor.execute_writeset(WriteSetPayload::Direct(GENESIS_CHANGE_SET_HEAD.clone()));
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    assert!(output
+        .events()
+        .iter()
+        .any(|event| event.is_new_epoch_event()));
+    executor.validate_genesis().unwrap();
+
+    // The applied state is usable by subsequent transactions.
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+    assert_eq!(
+        executor.execute_transaction(txn).status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
+
+#[test]
+fn apply_genesis_blobs_in_sequence() {
+    let mut executor = FakeExecutor::no_genesis();
+    executor.apply_genesis_blob(&bcs::to_bytes(&*GENESIS_CHANGE_SET_HEAD).unwrap());
+    executor.validate_genesis().unwrap();
+    assert_eq!(
+        executor.last_applied_write_set(),
+        Some(GENESIS_CHANGE_SET_HEAD.write_set())
+    );
+
+    let key = StateKey::raw(b"upgrade");
+    let upgrade_step = |value: u8| {
+        let write_set = WriteSetMut::new(vec![(
+            key.clone(),
+            WriteOp::legacy_creation(vec![value].into()),
+        )])
+        .freeze()
+        .unwrap();
+        bcs::to_bytes(&ChangeSet::new(write_set, vec![])).unwrap()
+    };
+    executor.apply_genesis_blob(&upgrade_step(1));
+    executor.apply_genesis_blob(&upgrade_step(2));
+    assert_eq!(
+        executor.read_state_value_bytes(&key).unwrap().to_vec(),
+        vec![2]
+    );
+    executor.validate_genesis().unwrap();
+}
@@ -2,13 +2,25 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_language_e2e_tests::executor::FakeExecutor;
-use aptos_types::account_config::CORE_CODE_ADDRESS;
+use aptos_language_e2e_tests::{
+    arguments::TransactionArgumentBuilder, executor::FakeExecutor, limits::VmLimits,
+};
+use aptos_types::{
+    account_config::{CoinStoreResource, CORE_CODE_ADDRESS},
+    on_chain_config::CurrentTimeMicroseconds,
+    state_store::state_key::StateKey,
+};
+use move_binary_format::errors::Location;
 use move_core_types::{
     account_address::AccountAddress,
+    ident_str,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
     value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
+use move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage};
+use move_vm_types::gas::UnmeteredGasMeter;
 
 #[test]
 fn test_timestamp_time_has_started() {
@@ -22,7 +34,9 @@ fn test_timestamp_time_has_started() {
         vec![],
         serialize_values(&vec![MoveValue::Signer(account_address)]),
     );
-    assert_eq!(output.unwrap_err().move_abort_code(), Some(327683));
+    let err = output.unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::ABORTED);
+    assert_eq!(err.sub_status(), Some(327683));
 
     executor.exec(
         "timestamp",
@@ -64,7 +78,221 @@ fn test_block_double_init() {
     );
 
     assert_eq!(
-        output.unwrap_err().status_code(),
+        output.unwrap_err().major_status(),
         StatusCode::RESOURCE_ALREADY_EXISTS
     );
 }
+
+#[test]
+fn test_argument_builder_checks_parameters() {
+    let block_module = aptos_cached_packages::head_release_bundle()
+        .compiled_modules()
+        .into_iter()
+        .find(|module| module.self_id().name().as_str() == "block")
+        .unwrap();
+
+    let args = TransactionArgumentBuilder::new()
+        .signer(CORE_CODE_ADDRESS)
+        .u64(1);
+    args.check_against(&block_module, "initialize").unwrap();
+
+    let mismatched = TransactionArgumentBuilder::new()
+        .address(CORE_CODE_ADDRESS)
+        .u64(1);
+    assert!(mismatched
+        .check_against(&block_module, "initialize")
+        .is_err());
+    assert!(TransactionArgumentBuilder::new()
+        .signer(CORE_CODE_ADDRESS)
+        .check_against(&block_module, "initialize")
+        .is_err());
+
+    let mut executor = FakeExecutor::stdlib_only_genesis();
+    executor.exec(
+        "account",
+        "create_account_unchecked",
+        vec![],
+        TransactionArgumentBuilder::new()
+            .address(CORE_CODE_ADDRESS)
+            .build(),
+    );
+    executor.exec("block", "initialize", vec![], args.build());
+}
+
+#[test]
+fn test_exec_with_limits() {
+    let mut executor = FakeExecutor::stdlib_only_genesis();
+    let args = serialize_values(&vec![MoveValue::Signer(CORE_CODE_ADDRESS)]);
+
+    let output = executor.try_exec_with_limits(
+        "timestamp",
+        "set_time_has_started",
+        vec![],
+        args.clone(),
+        VmLimits::unlimited().with_max_call_depth(1),
+    );
+    assert_eq!(
+        output.unwrap_err().major_status(),
+        StatusCode::CALL_STACK_OVERFLOW
+    );
+
+    let output = executor.try_exec_with_limits(
+        "timestamp",
+        "set_time_has_started",
+        vec![],
+        args.clone(),
+        VmLimits::unlimited().with_max_steps(1),
+    );
+    let err = output.unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::EXECUTION_LIMIT_REACHED);
+    assert!(err
+        .message()
+        .unwrap()
+        .contains("Exceeded the limit of 1 steps"));
+
+    executor
+        .try_exec_with_limits(
+            "timestamp",
+            "set_time_has_started",
+            vec![],
+            args,
+            VmLimits::unlimited()
+                .with_max_call_depth(16)
+                .with_max_steps(1_000),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_framework_at_custom_address() {
+    let framework_address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let mut executor = FakeExecutor::custom_genesis_at_address(
+        framework_address,
+        aptos_cached_packages::head_release_bundle(),
+    );
+    assert!(executor.list_modules(&CORE_CODE_ADDRESS).is_empty());
+    assert!(!executor.list_modules(&framework_address).is_empty());
+
+    let args = serialize_values(&vec![MoveValue::U64(1), MoveValue::U64(2)]);
+    executor
+        .try_exec("fixed_point32", "create_from_rational", vec![], args)
+        .unwrap();
+
+    let args = serialize_values(&vec![MoveValue::U64(1), MoveValue::U64(0)]);
+    let err = executor
+        .try_exec("fixed_point32", "create_from_rational", vec![], args)
+        .unwrap_err();
+    let module_id = ModuleId::new(framework_address, Identifier::new("fixed_point32").unwrap());
+    assert_eq!(err.location(), &Location::Module(module_id));
+    assert_eq!(err.sub_status(), Some(0x10001));
+}
+
+#[test]
+fn test_transfer_on_framework_at_custom_address() {
+    let framework_address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let mut executor = FakeExecutor::custom_genesis_at_address(
+        framework_address,
+        aptos_cached_packages::head_release_bundle(),
+    );
+    let alice = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+    let bob = AccountAddress::from_hex_literal("0xb0b").unwrap();
+
+    // `@aptos_framework` is relocated, so only the relocated framework account can do this.
+    let framework = MoveValue::Signer(framework_address);
+    executor.exec(
+        "aggregator_factory",
+        "initialize_aggregator_factory",
+        vec![],
+        serialize_values(&vec![framework.clone()]),
+    );
+    executor.exec(
+        "aptos_coin",
+        "initialize",
+        vec![],
+        serialize_values(&vec![framework.clone()]),
+    );
+    executor.exec(
+        "aptos_account",
+        "create_account",
+        vec![],
+        serialize_values(&vec![MoveValue::Address(alice)]),
+    );
+    executor.exec(
+        "aptos_coin",
+        "mint",
+        vec![],
+        serialize_values(&vec![
+            framework,
+            MoveValue::Address(alice),
+            MoveValue::U64(1_000),
+        ]),
+    );
+    executor.exec(
+        "aptos_account",
+        "transfer",
+        vec![],
+        serialize_values(&vec![
+            MoveValue::Signer(alice),
+            MoveValue::Address(bob),
+            MoveValue::U64(400),
+        ]),
+    );
+
+    let aptos_coin = StructTag {
+        address: framework_address,
+        module: Identifier::new("aptos_coin").unwrap(),
+        name: Identifier::new("AptosCoin").unwrap(),
+        type_args: vec![],
+    };
+    let coin_store = StructTag {
+        address: framework_address,
+        module: Identifier::new("coin").unwrap(),
+        name: Identifier::new("CoinStore").unwrap(),
+        type_args: vec![TypeTag::Struct(Box::new(aptos_coin))],
+    };
+    let balance = |address: AccountAddress| {
+        let bytes = executor
+            .read_state_value_bytes(&StateKey::resource(&address, &coin_store).unwrap())
+            .unwrap();
+        bcs::from_bytes::<CoinStoreResource>(&bytes).unwrap().coin()
+    };
+    assert_eq!(balance(alice), 600);
+    assert_eq!(balance(bob), 400);
+}
+
+#[test]
+fn test_with_session_shares_changes_between_calls() {
+    let mut executor = FakeExecutor::stdlib_only_genesis();
+    let timestamp = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("timestamp").unwrap());
+
+    let (now, write_set) = executor.with_session(|session| {
+        let storage = TraversalStorage::new();
+        session
+            .execute_function_bypass_visibility(
+                &timestamp,
+                ident_str!("set_time_has_started"),
+                vec![],
+                serialize_values(&vec![MoveValue::Signer(CORE_CODE_ADDRESS)]),
+                &mut UnmeteredGasMeter,
+                &mut TraversalContext::new(&storage),
+            )
+            .unwrap();
+        // The time published by the first call is visible to the second one.
+        session
+            .execute_function_bypass_visibility(
+                &timestamp,
+                ident_str!("now_microseconds"),
+                vec![],
+                Vec::<Vec<u8>>::new(),
+                &mut UnmeteredGasMeter,
+                &mut TraversalContext::new(&storage),
+            )
+            .unwrap()
+            .return_values
+    });
+
+    assert_eq!(now[0].0, 0u64.to_le_bytes());
+    let state_key = StateKey::on_chain_config::<CurrentTimeMicroseconds>().unwrap();
+    assert!(write_set.get(&state_key).is_some());
+    assert!(executor.read_state_value_bytes(&state_key).is_some());
+}
@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_language_e2e_tests::{common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use aptos_language_e2e_tests::{
+    common_transactions::peer_to_peer_txn,
+    executor::{FailBehavior, FakeExecutor, VmFailpoint},
+};
 use aptos_types::{
     account_address::AccountAddress,
     on_chain_config::FeatureFlag,
@@ -59,3 +62,91 @@ fn invariant_violation_error() {
         &TransactionStatus::Discard(DiscardedVMStatus::UNKNOWN_INVARIANT_VIOLATION_ERROR),
     );
 }
+
+#[test]
+fn injected_epilogue_error() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+
+    let guard = executor.set_failpoint(
+        VmFailpoint::Epilogue,
+        FailBehavior::Error(StatusCode::STORAGE_ERROR),
+    );
+    let output = executor.execute_transaction(txn.clone());
+    // The injected invariant violation is charged for, as CHARGE_INVARIANT_VIOLATION is enabled
+    // at genesis.
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::MiscellaneousError(Some(
+            StatusCode::STORAGE_ERROR
+        ))),
+    );
+
+    drop(guard);
+    let output = executor.execute_transaction(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
+
+#[test]
+fn injected_state_read_error() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+
+    {
+        let _guard = executor.set_failpoint(
+            VmFailpoint::StateRead,
+            FailBehavior::Error(StatusCode::STORAGE_ERROR),
+        );
+        // Every read from storage fails, so the transaction cannot even be validated.
+        let output = executor.execute_transaction(txn.clone());
+        assert!(output.status().is_discarded());
+        assert!(output.write_set().is_empty());
+    }
+
+    let output = executor.execute_transaction(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
+
+#[test]
+fn injected_event_emit_error() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+
+    {
+        let _guard = executor.set_failpoint(
+            VmFailpoint::EventEmit,
+            FailBehavior::Error(StatusCode::STORAGE_ERROR),
+        );
+        // Both the transfer and the failure epilogue, which emits the fee statement, emit
+        // events, so there is no way to keep the transaction.
+        let output = executor.execute_transaction(txn.clone());
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Discard(DiscardedVMStatus::STORAGE_ERROR)
+        );
+    }
+
+    let output = executor.execute_transaction(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
@@ -40,3 +40,37 @@ fn mint_to_new_account() {
         &TransactionStatus::Keep(ExecutionStatus::Success),
     );
 }
+
+#[test]
+fn read_aggregator_tracks_supply() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let mut root = Account::new_aptos_root();
+    let (private_key, public_key) = aptos_vm_genesis::GENESIS_KEYPAIR.clone();
+    root.rotate_key(private_key, public_key);
+
+    let coin_info = executor.read_coin_info_resource().unwrap();
+    let supply_aggregator = coin_info
+        .supply()
+        .as_ref()
+        .and_then(|supply| supply.aggregator.as_ref())
+        .expect("AptosCoin supply must be tracked by an aggregator");
+    let supply_before = executor.read_aggregator(supply_aggregator).unwrap();
+    assert_eq!(Some(supply_before), executor.read_coin_supply());
+
+    let new_account = executor.create_raw_account_data(0, 0);
+    executor.add_account_data(&new_account);
+    let txn = root
+        .transaction()
+        .payload(aptos_stdlib::aptos_coin_mint(*new_account.address(), 1_000))
+        .sequence_number(0)
+        .sign();
+    let output = executor.execute_and_apply(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success),
+    );
+
+    let supply_after = executor.read_aggregator(supply_aggregator).unwrap();
+    assert_eq!(supply_after, supply_before + 1_000);
+    assert_eq!(Some(supply_after), executor.read_coin_supply());
+}
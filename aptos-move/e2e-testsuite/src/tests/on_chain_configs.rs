@@ -3,13 +3,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_cached_packages::aptos_stdlib;
-use aptos_language_e2e_tests::{common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use aptos_language_e2e_tests::{
+    account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor,
+};
 use aptos_types::{
     account_config::CORE_CODE_ADDRESS,
-    on_chain_config::{AptosVersion, OnChainConfig},
-    transaction::TransactionStatus,
+    on_chain_config::{AptosVersion, Features, GasScheduleV2, OnChainConfig, StorageGasSchedule},
+    randomness::PerBlockRandomness,
+    transaction::{ExecutionStatus, SignedTransaction, TransactionStatus},
 };
 use aptos_vm::data_cache::AsMoveResolver;
+use std::panic::{self, AssertUnwindSafe};
 
 #[test]
 fn initial_aptos_version() {
@@ -65,3 +69,231 @@ fn drop_txn_after_reconfiguration() {
     let mut output = executor.execute_block(vec![txn, txn2]).unwrap();
     assert_eq!(output.pop().unwrap().status(), &TransactionStatus::Retry)
 }
+
+/// Returns an executor with a funded sender and receiver, and a block in which a transfer between
+/// them follows a reconfiguration.
+fn transfer_after_reconfiguration() -> (FakeExecutor, Vec<SignedTransaction>, AccountData) {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let reconfiguration = executor
+        .new_account_at(CORE_CODE_ADDRESS)
+        .transaction()
+        .payload(aptos_stdlib::aptos_governance_force_end_epoch())
+        .sequence_number(0)
+        .sign();
+    executor.new_block();
+
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let transfer = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+    (executor, vec![reconfiguration, transfer], receiver)
+}
+
+#[test]
+fn retry_txn_after_reconfiguration() {
+    // The transfer is retried as it comes after the reconfiguration.
+    let (mut executor, txns, _) = transfer_after_reconfiguration();
+    let err = executor.execute_block_with_retries(txns, 1).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "1 transaction(s) were still retried after 1 rounds"
+    );
+
+    let (mut executor, txns, receiver) = transfer_after_reconfiguration();
+    let outputs = executor.execute_block_with_retries(txns, 2).unwrap();
+    assert_eq!(outputs.len(), 2);
+    for output in outputs {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+    let receiver_balance = executor
+        .read_coin_store_resource(receiver.account())
+        .unwrap();
+    assert_eq!(receiver_balance.coin(), 101_000);
+}
+
+#[test]
+fn trigger_reconfiguration_advances_epoch() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let first_epoch = executor.trigger_reconfiguration().unwrap();
+    assert_eq!(executor.trigger_reconfiguration().unwrap(), first_epoch + 1);
+
+    executor.freeze_block_time(true);
+    assert!(executor.trigger_reconfiguration().is_err());
+}
+
+#[test]
+fn compare_gas_across_schedules() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let schedule = executor.current_gas_schedule();
+    let mut expensive = schedule.clone();
+    for (name, value) in expensive.entries.iter_mut() {
+        if name == "txn.min_transaction_gas_units" {
+            *value *= 2;
+        }
+    }
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1000, 0);
+    let gas_used = executor.compare_gas_across_schedules(txn, vec![schedule, expensive]);
+    assert!(gas_used[0] < gas_used[1]);
+}
+
+#[test]
+fn set_aptos_version_gates_version_bumps() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let version = aptos_types::on_chain_config::APTOS_MAX_KNOWN_VERSION;
+    assert_eq!(executor.aptos_version(), version.major);
+
+    executor.set_aptos_version(version.major + 10);
+    assert_eq!(executor.aptos_version(), version.major + 10);
+
+    // Bumping to a version that is not newer than the current one is rejected.
+    let account = executor.new_account_at(CORE_CODE_ADDRESS);
+    let txn = account
+        .transaction()
+        .payload(aptos_stdlib::version_set_for_next_epoch(version.major + 5))
+        .sequence_number(0)
+        .sign();
+    let output = executor.execute_transaction(txn);
+    assert!(matches!(
+        output.status(),
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { code: 0x1_0001, .. })
+    ));
+
+    let txn = account
+        .transaction()
+        .payload(aptos_stdlib::version_set_for_next_epoch(version.major + 11))
+        .sequence_number(0)
+        .sign();
+    let output = executor.execute_transaction(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
+
+#[test]
+fn block_randomness_seed_survives_new_block() {
+    let randomness_after_block = |seed: Vec<u8>| {
+        let mut executor = FakeExecutor::from_head_genesis();
+        executor.set_block_randomness_seed(seed);
+        executor.new_block();
+        PerBlockRandomness::fetch_config(&executor.get_state_view().as_move_resolver()).unwrap()
+    };
+
+    let randomness = randomness_after_block(vec![1; 32]);
+    assert_eq!(randomness.seed, Some(vec![1; 32]));
+    assert_eq!(randomness_after_block(vec![1; 32]), randomness);
+    assert_ne!(randomness_after_block(vec![2; 32]), randomness);
+}
+
+#[test]
+fn assert_configs_match_reports_changed_configs() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let before = executor.read_onchain_configs();
+    assert!(before.contains_key(&Features::CONFIG_ID));
+
+    executor.set_aptos_version(executor.aptos_version() + 1);
+    let after = executor.read_onchain_configs();
+    executor.assert_configs_match(&after);
+
+    // Only the version changed.
+    let mut expected = before.clone();
+    expected.insert(
+        AptosVersion::CONFIG_ID,
+        after[&AptosVersion::CONFIG_ID].clone(),
+    );
+    executor.assert_configs_match(&expected);
+
+    let err = panic::catch_unwind(AssertUnwindSafe(|| executor.assert_configs_match(&before)))
+        .unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert_eq!(message, "On-chain configs differ: Version changed");
+}
+
+#[test]
+fn set_storage_gas_params_overrides_only_storage_gas() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let before = executor.read_onchain_configs();
+    assert!(executor.storage_gas_params().is_some());
+
+    let params = StorageGasSchedule {
+        per_item_read: 1,
+        per_item_create: 2,
+        per_item_write: 3,
+        per_byte_read: 4,
+        per_byte_create: 5,
+        per_byte_write: 6,
+    };
+    executor.set_storage_gas_params(params.clone());
+    assert_eq!(executor.storage_gas_params(), Some(params));
+    // At the latest gas feature version, the storage prices are read from the gas schedule.
+    let storage_io_entries: Vec<_> = executor
+        .current_gas_schedule()
+        .entries
+        .into_iter()
+        .filter(|(name, _)| name.starts_with("txn.storage_io_per_state_"))
+        .collect();
+    assert_eq!(storage_io_entries, vec![
+        ("txn.storage_io_per_state_slot_read".to_string(), 1),
+        ("txn.storage_io_per_state_byte_read".to_string(), 4),
+        ("txn.storage_io_per_state_slot_write".to_string(), 3),
+        ("txn.storage_io_per_state_byte_write".to_string(), 6),
+    ]);
+
+    let after = executor.read_onchain_configs();
+    let mut expected = before;
+    for config_id in [StorageGasSchedule::CONFIG_ID, GasScheduleV2::CONFIG_ID] {
+        expected.insert(config_id, after[&config_id].clone());
+    }
+    executor.assert_configs_match(&expected);
+}
+
+#[test]
+fn storage_gas_params_price_writes() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+
+    let fee_statement = |executor: &mut FakeExecutor, per_item_write, per_byte_write| {
+        executor.set_storage_gas_params(StorageGasSchedule {
+            per_item_read: 0,
+            per_item_create: 0,
+            per_item_write,
+            per_byte_read: 0,
+            per_byte_create: 0,
+            per_byte_write,
+        });
+        let output = executor.execute_transaction(txn.clone());
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+        output.try_extract_fee_statement().unwrap().unwrap()
+    };
+
+    let free = fee_statement(&mut executor, 0, 0);
+    // 1 gas unit per slot written, and then per byte written.
+    for expensive in [
+        fee_statement(&mut executor, 1_000_000, 0),
+        fee_statement(&mut executor, 0, 1_000_000),
+    ] {
+        assert!(expensive.io_gas_used() > free.io_gas_used());
+        assert_eq!(expensive.execution_gas_used(), free.execution_gas_used());
+        assert_eq!(
+            expensive.gas_used() - free.gas_used(),
+            expensive.io_gas_used() - free.io_gas_used()
+        );
+    }
+}
@@ -2,14 +2,28 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_cached_packages::aptos_stdlib;
 use aptos_language_e2e_tests::{
-    account::Account, common_transactions::peer_to_peer_txn, executor::FakeExecutor,
+    account::Account,
+    common_transactions::peer_to_peer_txn,
+    data_store::RecordingStateView,
+    executor::{AccessOp, EventHandleKind, ExecutorMode, FakeExecutor, TxnStatusKind},
 };
+use aptos_temppath::TempPath;
 use aptos_types::{
-    account_config::{DepositEvent, WithdrawEvent},
-    transaction::{ExecutionStatus, SignedTransaction, TransactionOutput, TransactionStatus},
+    account_config::{AccountResource, DepositEvent, WithdrawEvent},
+    state_store::state_key::StateKey,
+    transaction::{
+        ExecutionStatus, SignedTransaction, Transaction, TransactionOutput, TransactionStatus,
+    },
+};
+use move_core_types::account_address::AccountAddress;
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    panic::{self, AssertUnwindSafe},
+    time::Instant,
 };
-use std::{convert::TryFrom, time::Instant};
 
 #[test]
 fn single_peer_to_peer_with_event() {
@@ -61,6 +75,251 @@ fn single_peer_to_peer_with_event() {
     }
 }
 
+#[test]
+fn peer_to_peer_advances_event_handle_counts() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    for seq_num in 10..13 {
+        let sent_before = executor
+            .read_event_handle_count(sender.account(), EventHandleKind::Sent)
+            .expect("sender coin store must exist");
+        let txn = peer_to_peer_txn(sender.account(), receiver.account(), seq_num, 1_000, 0);
+        executor.execute_and_apply(txn);
+        let sent_after = executor
+            .read_event_handle_count(sender.account(), EventHandleKind::Sent)
+            .expect("sender coin store must exist");
+        assert_eq!(sent_before + 1, sent_after);
+    }
+    assert_eq!(
+        executor.read_event_handle_count(receiver.account(), EventHandleKind::Received),
+        Some(3)
+    );
+}
+
+#[test]
+fn transfer_helpers() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(100_000);
+
+    executor.transfer(&sender, &receiver, 1_000);
+    executor.transfer(&sender, &receiver, 1_000);
+    let receiver_balance = executor
+        .read_coin_store_resource(&receiver)
+        .expect("receiver balance must exist");
+    assert_eq!(102_000, receiver_balance.coin());
+    assert_eq!(
+        2,
+        executor
+            .read_account_resource(&sender)
+            .expect("sender must exist")
+            .sequence_number()
+    );
+
+    // Transferring more than the balance aborts, but still bumps the sequence number.
+    let status = executor
+        .try_transfer(&sender, &receiver, 10_000_000)
+        .unwrap_err();
+    assert!(matches!(
+        status,
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { .. })
+    ));
+    assert_eq!(
+        3,
+        executor
+            .read_account_resource(&sender)
+            .expect("sender must exist")
+            .sequence_number()
+    );
+}
+
+#[test]
+fn transfer_between_genesis_accounts() {
+    let alice = Account::new_genesis_account(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let bob = Account::new_genesis_account(AccountAddress::from_hex_literal("0xb0b").unwrap());
+    let mut executor = FakeExecutor::from_genesis_with_accounts(&[
+        (*alice.address(), 1_000_000),
+        (*bob.address(), 100_000),
+    ]);
+
+    executor.transfer(&alice, &bob, 1_000);
+    let bob_balance = executor
+        .read_coin_store_resource(&bob)
+        .expect("receiver balance must exist");
+    assert_eq!(101_000, bob_balance.coin());
+}
+
+#[test]
+fn parallel_conflicting_transfers_commit_in_order() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(10_000_000, 10);
+    let receiver = executor.create_raw_account_data(3_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // All transfers are sent by the same account, so each one reads the sequence number written
+    // by the previous one. Transactions executed speculatively before their predecessor finished
+    // read a stale sequence number and fail validation.
+    let num_txns = 8;
+    let txns = (0..num_txns)
+        .map(|i| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                10 + i,
+                1_000,
+                0,
+            ))
+        })
+        .collect();
+    let (outputs, stats) = executor.execute_transaction_block_parallel_with_stats(txns);
+    for output in &outputs {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+    assert_eq!(stats.commit_order, (0..num_txns as u32).collect::<Vec<_>>());
+    assert!(stats.num_reexecutions <= stats.num_conflicts);
+    // With a single CPU, the block is executed sequentially and nothing can conflict.
+    if std::thread::available_parallelism().map_or(1, |n| n.get()) > 1 {
+        assert!(stats.num_conflicts > 0);
+    }
+}
+
+#[test]
+fn analyze_transfer_conflicts() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(4, 1_000_000, 0);
+
+    // Disjoint senders and receivers do not conflict.
+    let disjoint = vec![
+        peer_to_peer_txn(&accounts[0], &accounts[1], 0, 1_000, 0),
+        peer_to_peer_txn(&accounts[2], &accounts[3], 0, 1_000, 0),
+    ];
+    assert!(executor
+        .analyze_block_conflicts(&disjoint)
+        .is_conflict_free());
+
+    // Both transfers pay to the same receiver.
+    let shared_receiver = vec![
+        peer_to_peer_txn(&accounts[0], &accounts[1], 0, 1_000, 0),
+        peer_to_peer_txn(&accounts[2], &accounts[1], 0, 1_000, 0),
+    ];
+    let graph = executor.analyze_block_conflicts(&shared_receiver);
+    assert_eq!(graph.conflicts_of(0), vec![1]);
+    assert_eq!(graph.conflicts_of(1), vec![0]);
+}
+
+#[test]
+fn transfer_reads_then_sorted_writes() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(100_000);
+
+    let txn = peer_to_peer_txn(&sender, &receiver, 0, 1_000, 0);
+    let (output, accesses) = executor.execute_transaction_with_reads_then_writes(txn);
+    let first_write = accesses
+        .iter()
+        .position(|op| matches!(op, AccessOp::Write(_)))
+        .expect("a transfer must write");
+    assert!(accesses[first_write..]
+        .iter()
+        .all(|op| matches!(op, AccessOp::Write(_))));
+    let writes = accesses
+        .iter()
+        .filter_map(|op| match op {
+            AccessOp::Write(key) => Some(key.clone()),
+            AccessOp::Read(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        writes,
+        output
+            .write_set()
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>()
+    );
+
+    let account_key = StateKey::resource_typed::<AccountResource>(sender.address()).unwrap();
+    let first_read = accesses
+        .iter()
+        .position(|op| op == &AccessOp::Read(account_key.clone()))
+        .expect("sender account must be read");
+    let write = accesses
+        .iter()
+        .position(|op| op == &AccessOp::Write(account_key.clone()))
+        .expect("sender account must be written");
+    assert!(first_read < write);
+}
+
+#[test]
+fn replay_transfer_against_captured_reads() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(100_000);
+
+    let txn = peer_to_peer_txn(&sender, &receiver, 0, 1_000, 0);
+    let (output, accesses) = executor.execute_transaction_with_reads_then_writes(txn.clone());
+    let reads = accesses
+        .into_iter()
+        .filter_map(|op| match op {
+            AccessOp::Read(key) => executor
+                .read_state_value_bytes(&key)
+                .map(|bytes| (key, bytes.to_vec())),
+            AccessOp::Write(_) => None,
+        })
+        .collect();
+
+    let replayed = executor.replay_against(txn, reads);
+    assert_eq!(replayed.status(), output.status());
+    assert_eq!(replayed.write_set(), output.write_set());
+}
+
+#[test]
+fn transfer_native_calls() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(100_000);
+    assert!(executor.last_native_calls().is_empty());
+
+    executor.collect_native_calls(true);
+    executor.execute_transaction(peer_to_peer_txn(&sender, &receiver, 0, 1_000, 0));
+    let native_calls = executor.last_native_calls();
+    assert!(native_calls
+        .iter()
+        .any(|(name, count)| name == "0x1::signer::borrow_address" && *count > 0));
+}
+
+#[test]
+fn block_gas_is_burnt_from_supply() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(3, 100_000_000, 0);
+    let gas_unit_price = 100;
+    let txns = vec![
+        peer_to_peer_txn(&accounts[0], &accounts[2], 0, 1_000, gas_unit_price),
+        peer_to_peer_txn(&accounts[1], &accounts[2], 0, 1_000, gas_unit_price),
+    ];
+
+    let supply_before = executor.read_coin_supply().unwrap();
+    let (outputs, total_gas) = executor.execute_and_apply_block_with_gas(txns);
+    let supply_after = executor.read_coin_supply().unwrap();
+
+    assert_eq!(
+        total_gas,
+        outputs.iter().map(TransactionOutput::gas_used).sum::<u64>()
+    );
+    assert_eq!(
+        supply_before - supply_after,
+        (total_gas * gas_unit_price) as u128
+    );
+}
+
 #[test]
 fn few_peer_to_peer_with_event() {
     let mut executor = FakeExecutor::from_head_genesis();
@@ -451,3 +710,346 @@ fn many_to_one_peer_to_peer() {
     println!("EXECUTION TIME: {}", execution_time);
     print_accounts(&executor, &accounts);
 }
+
+#[test]
+fn disabled_parallel_check_skips_parallel_execution() {
+    let _scenario = fail::FailScenario::setup();
+    // Fails the parallel executor once every transaction is committed, and panics instead of
+    // falling back to sequential execution. The sequential executor does not reach this point.
+    fail::cfg("commit-all-halt-err", "return()").unwrap();
+
+    let mut executor = FakeExecutor::from_head_genesis().set_parallel();
+    executor.disable_block_executor_fallback();
+    let sender = executor.create_raw_account_data(3_000_000, 10);
+    let receiver = executor.create_raw_account_data(3_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = || peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+
+    executor.set_parallel_check(false);
+    let outputs = executor.execute_block(vec![txn()]).unwrap();
+    assert_eq!(
+        outputs[0].status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    executor.set_parallel_check(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| executor.execute_block(vec![txn()])));
+    assert!(result.is_err());
+}
+
+#[test]
+fn block_timings_per_transaction() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(3, 1_000_000, 0);
+    let txns = vec![
+        peer_to_peer_txn(&accounts[0], &accounts[2], 0, 1_000, 0),
+        peer_to_peer_txn(&accounts[1], &accounts[2], 0, 1_000, 0),
+    ];
+
+    executor.execute_block(txns.clone()).unwrap();
+    assert!(executor.last_block_timings().is_empty());
+
+    executor.collect_timings(true);
+    let outputs = executor.execute_block(txns.clone()).unwrap();
+    assert_eq!(executor.last_block_timings().len(), outputs.len());
+
+    // The parallel executor records no timings, and drops the ones of the previous block.
+    let executor = executor.set_executor_mode(ExecutorMode::ParallelOnly);
+    executor.execute_block(txns).unwrap();
+    assert!(executor.last_block_timings().is_empty());
+}
+
+#[test]
+fn transfer_read_count() {
+    let mut executor =
+        FakeExecutor::from_head_genesis().set_executor_mode(ExecutorMode::SequentialOnly);
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(100_000);
+    assert_eq!(executor.last_txn_read_count(), 0);
+    let txn = peer_to_peer_txn(&sender, &receiver, 0, 1_000, 0);
+
+    // Warm up the loaded modules, so that every run below makes the same reads.
+    executor.execute_transaction(txn.clone());
+    let output = executor.execute_transaction(txn.clone());
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    let state_view = RecordingStateView::new(executor.data_store());
+    executor
+        .execute_transaction_block_with_state_view(
+            vec![Transaction::UserTransaction(txn)],
+            &state_view,
+        )
+        .unwrap();
+    let reads = state_view.into_reads();
+    let distinct_reads = reads.iter().collect::<HashSet<_>>();
+    assert_eq!(executor.last_txn_read_count(), distinct_reads.len());
+}
+
+#[test]
+fn transfer_output_matches_reference_file() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(100_000);
+    let txn = peer_to_peer_txn(&sender, &receiver, 0, 1_000, 0);
+
+    let reference = TempPath::new();
+    executor.write_output_to_file(&executor.execute_transaction(txn.clone()), reference.path());
+    executor.assert_output_matches_file(&executor.execute_transaction(txn), reference.path());
+
+    // Only the transferred amount, and thus the written balances, differ.
+    let other = executor.execute_transaction(peer_to_peer_txn(&sender, &receiver, 0, 2_000, 0));
+    let err = panic::catch_unwind(AssertUnwindSafe(|| {
+        executor.assert_output_matches_file(&other, reference.path())
+    }))
+    .unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.contains("Write for"), "{}", message);
+}
+
+#[test]
+fn memory_trace_records_transfers() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let accounts = executor.create_accounts(3, 1_000_000, 0);
+    let txns = vec![
+        Transaction::UserTransaction(peer_to_peer_txn(&accounts[0], &accounts[2], 0, 1_000, 0)),
+        Transaction::UserTransaction(peer_to_peer_txn(&accounts[1], &accounts[2], 0, 1_000, 0)),
+    ];
+    executor.execute_transaction_block(txns.clone()).unwrap();
+    assert!(executor.take_trace().is_empty());
+
+    executor.enable_memory_trace();
+    let outputs = executor.execute_transaction_block(txns.clone()).unwrap();
+    let trace = executor.take_trace();
+    assert_eq!(trace.len(), 2);
+    let sender_key = StateKey::resource_typed::<AccountResource>(accounts[0].address()).unwrap();
+    for ((traced, txn), output) in trace.iter().zip(&txns).zip(&outputs) {
+        assert_eq!(&traced.input, txn);
+        assert!(traced.pre_state_keys.contains(&sender_key));
+        assert_eq!(traced.output.as_ref(), Ok(output));
+    }
+    assert!(executor.take_trace().is_empty());
+}
+
+#[test]
+fn peer_to_peer_is_deterministic() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0);
+    executor.assert_deterministic(txn, 3);
+}
+
+#[test]
+fn build_and_sign_tracks_sequence_numbers() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(0);
+
+    for _ in 0..3 {
+        let txn = executor.build_and_sign(
+            &sender,
+            aptos_stdlib::aptos_coin_transfer(*receiver.address(), 10),
+        );
+        let output = executor.execute_and_apply(txn);
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+    assert_eq!(
+        executor
+            .read_account_resource(&sender)
+            .unwrap()
+            .sequence_number(),
+        3
+    );
+}
+
+#[test]
+fn resilient_block_execution_agrees_on_transfers() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(3_000_000, 10);
+    let receiver = executor.create_raw_account_data(3_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txns = (0..2)
+        .map(|i| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                10 + i,
+                1_000,
+                0,
+            ))
+        })
+        .collect();
+    let result = executor.execute_transaction_block_resilient(txns);
+    assert_eq!(result.parallel_discrepancy, None);
+    for output in result.output.unwrap() {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+}
+
+#[test]
+fn resilient_block_execution_survives_parallel_failure() {
+    let _scenario = fail::FailScenario::setup();
+    // Fails the parallel executor once every transaction is committed, and panics instead of
+    // falling back to sequential execution. The sequential executor does not reach this point.
+    fail::cfg("commit-all-halt-err", "return()").unwrap();
+
+    let mut executor = FakeExecutor::from_head_genesis();
+    executor.disable_block_executor_fallback();
+    let sender = executor.create_raw_account_data(3_000_000, 10);
+    let receiver = executor.create_raw_account_data(3_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txns = (0..2)
+        .map(|i| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                10 + i,
+                1_000,
+                0,
+            ))
+        })
+        .collect();
+    let result = executor.execute_transaction_block_resilient(txns);
+    assert_eq!(
+        result.parallel_discrepancy.as_deref(),
+        Some("Parallel execution panicked: Parallel execution failed and fallback is not allowed")
+    );
+    let outputs = result.output.unwrap();
+    assert_eq!(outputs.len(), 2);
+    for output in outputs {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+}
+
+#[test]
+fn transfer_until_target_balance() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(0);
+    let balance =
+        |executor: &FakeExecutor| executor.read_coin_store_resource(&receiver).unwrap().coin();
+
+    let transfer = |executor: &mut FakeExecutor| {
+        executor.build_and_sign(
+            &sender,
+            aptos_stdlib::aptos_coin_transfer(*receiver.address(), 100),
+        )
+    };
+    let iters = executor
+        .execute_until(transfer, |executor| balance(executor) >= 250, 10)
+        .unwrap();
+    assert_eq!(iters, 3);
+    assert_eq!(balance(&executor), 300);
+
+    assert!(executor
+        .execute_until(transfer, |executor| balance(executor) >= 1_000, 2)
+        .is_err());
+}
+
+#[test]
+fn fold_transfers_with_step() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(0);
+
+    let (executor, outputs) =
+        (0..3).fold((executor, vec![]), |(executor, mut outputs), seq_num| {
+            let txn = peer_to_peer_txn(&sender, &receiver, seq_num, 100, 0);
+            let (executor, output) = executor.step(txn);
+            outputs.push(output);
+            (executor, outputs)
+        });
+
+    assert!(outputs
+        .iter()
+        .all(|output| output.status() == &TransactionStatus::Keep(ExecutionStatus::Success)));
+    let receiver_balance = executor.read_coin_store_resource(&receiver).unwrap().coin();
+    assert_eq!(receiver_balance, 300);
+}
+
+#[test]
+fn batch_transfer_gas_is_monotonic() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+
+    executor.assert_gas_monotonic(
+        |size| {
+            let recipients = (0..size).map(|_| AccountAddress::random()).collect();
+            executor.build_and_sign(
+                &sender,
+                aptos_stdlib::aptos_account_batch_transfer(recipients, vec![1; size]),
+            )
+        },
+        &[1, 2, 4, 8],
+    );
+}
+
+#[test]
+fn transfer_balance_change_excludes_gas() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    executor.set_default_gas_params(100_000, 100);
+    let sender = executor.new_funded_account(100_000_000);
+    let receiver = executor.new_funded_account(0);
+    let bystander = executor.new_funded_account(1_000);
+
+    let txn = executor.build_and_sign(
+        &sender,
+        aptos_stdlib::aptos_coin_transfer(*receiver.address(), 1_234),
+    );
+    executor.assert_balance_change(
+        txn,
+        &[(&sender, -1_234), (&receiver, 1_234), (&bystander, 0)],
+    );
+}
+
+#[test]
+fn block_summary_of_transfers() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(0, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txns = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000, 0),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000, 0),
+        // More than the sender has left.
+        peer_to_peer_txn(sender.account(), receiver.account(), 12, 10_000_000, 0),
+    ];
+    let summary = executor.execute_block_summary(txns);
+    let kinds: Vec<_> = summary.iter().map(|txn| txn.status_kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TxnStatusKind::Success,
+            TxnStatusKind::Success,
+            TxnStatusKind::MoveAbort,
+        ]
+    );
+    assert_eq!(summary[0].abort_code, None);
+    assert!(summary[0].num_writes > 0 && summary[0].num_events > 0);
+    let abort_code = summary[2].abort_code.unwrap();
+    assert!(summary[2]
+        .to_string()
+        .starts_with(&format!("#2: MoveAbort (code {})", abort_code)));
+}
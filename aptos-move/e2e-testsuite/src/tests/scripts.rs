@@ -18,6 +18,7 @@ use move_core_types::{
     language_storage::{StructTag, TypeTag},
     vm_status::{StatusCode, StatusCode::LINKER_ERROR},
 };
+use move_ir_compiler::Compiler;
 
 #[test]
 fn script_code_unverifiable() {
@@ -521,3 +522,116 @@ fn forbid_script_emitting_events() {
     assert_eq!(balance, updated_sender_balance.coin());
     assert_eq!(11, updated_sender.sequence_number());
 }
+
+fn compile_script(code: &str) -> Vec<u8> {
+    let modules = aptos_cached_packages::head_release_bundle().compiled_modules();
+    let compiler = Compiler {
+        deps: modules.iter().collect(),
+    };
+    compiler.into_script_blob(code).expect("Failed to compile")
+}
+
+#[test]
+fn exec_script_applies_its_effects() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    executor.collect_native_calls(true);
+    let sender = executor.create_raw_account_data(1_000_000, 0);
+    let receiver = executor.create_raw_account_data(1_000, 0);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let script = compile_script(
+        "
+    import 0x1.aptos_account;
+
+    main(account: signer, to: address, amount: u64) {
+    label b0:
+      aptos_account.transfer(&account, move(to), move(amount));
+      return;
+    }
+",
+    );
+    let (_write_set, events) = executor
+        .exec_script(
+            script,
+            vec![],
+            vec![
+                bcs::to_bytes(receiver.address()).unwrap(),
+                bcs::to_bytes(&100u64).unwrap(),
+            ],
+            vec![*sender.address()],
+        )
+        .unwrap();
+    assert!(!events.is_empty());
+
+    // The script runs without gas, so only the transferred amount moves.
+    let sender_balance = executor.read_coin_store_resource(sender.account()).unwrap();
+    assert_eq!(sender_balance.coin(), 999_900);
+    let receiver_balance = executor
+        .read_coin_store_resource(receiver.account())
+        .unwrap();
+    assert_eq!(receiver_balance.coin(), 1_100);
+    assert!(executor
+        .last_native_calls()
+        .iter()
+        .any(|(name, count)| name == "0x1::signer::borrow_address" && *count > 0));
+}
+
+#[test]
+fn exec_script_reports_verification_errors() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.create_raw_account_data(1_000_000, 0);
+    executor.add_account_data(&sender);
+
+    let mut script = empty_script();
+    script.code.code = vec![Bytecode::LdU8(0), Bytecode::Add, Bytecode::Ret];
+    let mut blob = vec![];
+    script.serialize(&mut blob).expect("script must serialize");
+
+    let status = executor
+        .exec_script(blob, vec![], vec![], vec![*sender.address()])
+        .unwrap_err();
+    assert_eq!(
+        status.status_code(),
+        StatusCode::NEGATIVE_STACK_SIZE_WITHIN_BLOCK
+    );
+    let sender_balance = executor.read_coin_store_resource(sender.account()).unwrap();
+    assert_eq!(sender_balance.coin(), 1_000_000);
+}
+
+#[test]
+fn debug_print_output_is_captured() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let script = compile_script(
+        "
+    import 0x1.debug;
+
+    main(account: signer) {
+        let x: u64;
+    label b0:
+        x = 42;
+        debug.print<u64>(&x);
+        return;
+    }
+",
+    );
+
+    executor
+        .exec_script(script.clone(), vec![], vec![], vec![*sender.address()])
+        .unwrap();
+    assert_eq!(executor.take_debug_output(), vec!["[debug] 42".to_string()]);
+    assert!(executor.take_debug_output().is_empty());
+
+    let txn = sender
+        .transaction()
+        .script(Script::new(script, vec![], vec![]))
+        .sequence_number(0)
+        .sign();
+    let output = executor.execute_transaction(txn);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    assert_eq!(executor.take_debug_output(), vec!["[debug] 42".to_string()]);
+}
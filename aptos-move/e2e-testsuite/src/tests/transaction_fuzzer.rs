@@ -3,7 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_cached_packages::aptos_stdlib::EntryFunctionCall;
-use aptos_language_e2e_tests::{account::Account, executor::FakeExecutor};
+use aptos_language_e2e_tests::{
+    account::Account,
+    executor::{ExecutionClassification, FakeExecutor},
+};
 use proptest::{collection::vec, prelude::*};
 
 proptest! {
@@ -29,4 +32,11 @@ proptest! {
                 prop_assert!(!output.status().is_discarded());
         }
     }
+
+    #[test]
+    fn fuzz_raw_txn_bytes(bytes in vec(any::<u8>(), 0..512)) {
+        let executor = FakeExecutor::from_head_genesis();
+        let classification = executor.execute_raw_txn_bytes(&bytes);
+        prop_assert!(!matches!(classification, ExecutionClassification::Keep(_)));
+    }
 }
@@ -15,8 +15,10 @@ use aptos_types::{
     account_config,
     chain_id::ChainId,
     test_helpers::transaction_test_helpers,
-    transaction::{ExecutionStatus, Script, TransactionArgument, TransactionStatus},
-    vm_status::StatusCode,
+    transaction::{
+        ExecutionStatus, Script, TransactionArgument, TransactionPayload, TransactionStatus,
+    },
+    vm_status::{StatusCode, VMStatus},
 };
 use move_binary_format::file_format::CompiledModule;
 use move_core_types::{
@@ -782,3 +784,133 @@ fn test_type_tag_transitive_dependency_fails_verification() {
         _ => panic!("Kept transaction with an invariant violation!"),
     }
 }
+
+#[test]
+fn epilogue_fee_boundaries() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+
+    // No gas used at all.
+    assert!(executor.run_epilogue(*sender.address(), 100, 100, 100).is_ok());
+
+    // The fee does not fit into a u64.
+    let status = executor.run_epilogue(*sender.address(), u64::MAX, 0, 2).unwrap_err();
+    assert!(matches!(status, VMStatus::MoveAbort(..)));
+}
+
+#[test]
+fn verify_multi_ed25519_threshold() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let (sender, private_keys) = executor.create_multisig_account(2, 3, 1_000_000);
+    let receiver = executor.new_funded_account(1_000);
+    let txn = || {
+        sender
+            .transaction()
+            .payload(aptos_stdlib::aptos_coin_transfer(*receiver.address(), 100))
+            .sequence_number(0)
+    };
+
+    let below_threshold = txn().sign_multi_ed25519(&[(0, &private_keys[0])]);
+    assert_eq!(
+        executor.validate_transaction(below_threshold).status(),
+        Some(StatusCode::INVALID_SIGNATURE)
+    );
+
+    let at_threshold = txn().sign_multi_ed25519(&[(0, &private_keys[0]), (2, &private_keys[2])]);
+    assert_eq!(executor.validate_transaction(at_threshold).status(), None);
+}
+
+#[test]
+fn verify_max_transaction_size_boundary() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(1_000);
+    let txn = sender
+        .transaction()
+        .payload(aptos_stdlib::aptos_coin_transfer(*receiver.address(), 100))
+        .sequence_number(0)
+        .sign();
+    let txn_size = txn.raw_txn_bytes_len() as u64;
+
+    executor.set_max_transaction_size(txn_size);
+    assert_eq!(executor.validate_transaction(txn.clone()).status(), None);
+
+    executor.set_max_transaction_size(txn_size - 1);
+    assert_eq!(
+        executor.validate_transaction(txn).status(),
+        Some(StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE)
+    );
+}
+
+#[test]
+fn verify_publishing_option() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let sender = executor.new_funded_account(1_000_000);
+    let receiver = executor.new_funded_account(1_000);
+
+    let transfer = sender
+        .transaction()
+        .payload(aptos_stdlib::aptos_coin_transfer(*receiver.address(), 100))
+        .sequence_number(0)
+        .sign();
+    assert!(executor.check_publishing_option(&transfer).is_ok());
+
+    let script = sender
+        .transaction()
+        .script(Script::new(EMPTY_SCRIPT.to_vec(), vec![], vec![]))
+        .sequence_number(0)
+        .sign();
+    assert!(executor.check_publishing_option(&script).is_ok());
+
+    // The deprecated payload cannot be constructed directly, only deserialized.
+    let module_bundle: TransactionPayload = bcs::from_bytes(&[1, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+    assert!(matches!(module_bundle, TransactionPayload::ModuleBundle(_)));
+    let module_bundle = sender
+        .transaction()
+        .payload(module_bundle)
+        .sequence_number(0)
+        .sign();
+    assert_eq!(
+        executor
+            .check_publishing_option(&module_bundle)
+            .unwrap_err()
+            .status_code(),
+        StatusCode::FEATURE_UNDER_GATING
+    );
+}
+
+#[test]
+fn verify_transactions_in_input_order() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let senders = executor.create_accounts(4, 1_000_000, 10);
+    let receiver = executor.new_funded_account(1_000);
+    // Every other transaction has a stale sequence number.
+    let txns = senders
+        .iter()
+        .enumerate()
+        .map(|(idx, sender)| {
+            sender
+                .transaction()
+                .payload(aptos_stdlib::aptos_coin_transfer(*receiver.address(), 100))
+                .sequence_number(if idx % 2 == 0 { 10 } else { 9 })
+                .sign()
+        })
+        .collect::<Vec<_>>();
+
+    let expected = txns
+        .iter()
+        .map(|txn| executor.validate_transaction(txn.clone()).status())
+        .collect::<Vec<_>>();
+    assert_eq!(expected, vec![
+        None,
+        Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD),
+        None,
+        Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD),
+    ]);
+    let statuses = executor
+        .validate_transactions(txns)
+        .iter()
+        .map(|result| result.status())
+        .collect::<Vec<_>>();
+    assert_eq!(statuses, expected);
+}
@@ -18,7 +18,39 @@ use move_vm_types::{
     values::{Reference, Struct, Value},
 };
 use smallvec::{smallvec, SmallVec};
-use std::collections::VecDeque;
+use std::{cell::RefCell, collections::VecDeque};
+
+thread_local! {
+    /// When set, output of the debug natives on this thread is appended here instead of being
+    /// printed to stdout.
+    static DEBUG_OUTPUT_SINK: RefCell<Option<Vec<String>>> = RefCell::new(None);
+}
+
+/// Starts (or stops) capturing output of `debug::print` and friends executed on the current
+/// thread. Stopping discards anything that was captured but not yet taken.
+pub fn capture_debug_output(enabled: bool) {
+    DEBUG_OUTPUT_SINK.with(|sink| *sink.borrow_mut() = enabled.then(Vec::new));
+}
+
+/// Returns the debug output captured on the current thread since the last call, leaving the
+/// capture enabled.
+pub fn take_debug_output() -> Vec<String> {
+    DEBUG_OUTPUT_SINK.with(|sink| {
+        sink.borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    })
+}
+
+/// Either records the line in the capture sink or prints it, depending on whether capture is
+/// enabled on the current thread.
+fn emit_debug_output(line: String) {
+    DEBUG_OUTPUT_SINK.with(|sink| match sink.borrow_mut().as_mut() {
+        Some(output) => output.push(line),
+        None => println!("{}", line),
+    })
+}
 
 /***************************************************************************************************
  * native fun print
@@ -37,10 +69,10 @@ fn native_print(
         let val = safely_pop_arg!(args, Struct);
         let bytes = val.unpack()?.next().unwrap();
 
-        println!(
+        emit_debug_output(format!(
             "[debug] {}",
             std::str::from_utf8(&bytes.value_as::<Vec<u8>>()?).unwrap()
-        );
+        ));
     }
 
     Ok(smallvec![])
@@ -80,10 +112,10 @@ fn native_old_debug_print(
         let x = safely_pop_arg!(args, Reference);
         let val = x.read_ref().map_err(SafeNativeError::InvariantViolation)?;
 
-        println!(
+        emit_debug_output(format!(
             "[debug] {}",
             native_format_debug(context, &ty_args[0], val)?
-        );
+        ));
     }
     Ok(smallvec![])
 }
@@ -100,7 +132,7 @@ fn native_old_print_stacktrace(
     if cfg!(feature = "testing") {
         let mut s = String::new();
         context.print_stack_trace(&mut s)?;
-        println!("{}", s);
+        emit_debug_output(s);
     }
     Ok(smallvec![])
 }
@@ -970,10 +970,18 @@ pub fn generate_test_genesis(
     count: Option<usize>,
 ) -> (ChangeSet, Vec<TestValidator>) {
     let test_validators = TestValidator::new_test_set(count, Some(100_000_000));
-    let validators_: Vec<Validator> = test_validators.iter().map(|t| t.data.clone()).collect();
-    let validators = &validators_;
+    let validators: Vec<Validator> = test_validators.iter().map(|t| t.data.clone()).collect();
+    let genesis = generate_test_genesis_with_validators(framework, &validators);
+    (genesis, test_validators)
+}
 
-    let genesis = encode_genesis_change_set(
+/// Generate a testing genesis `ChangeSet` with the given validators, using the same
+/// configuration as [`generate_test_genesis`].
+pub fn generate_test_genesis_with_validators(
+    framework: &ReleaseBundle,
+    validators: &[Validator],
+) -> ChangeSet {
+    encode_genesis_change_set(
         &GENESIS_KEYPAIR.1,
         validators,
         framework,
@@ -1000,8 +1008,7 @@ pub fn generate_test_genesis(
         &OnChainConsensusConfig::default_for_genesis(),
         &OnChainExecutionConfig::default_for_genesis(),
         &default_gas_schedule(),
-    );
-    (genesis, test_validators)
+    )
 }
 
 pub fn generate_mainnet_genesis(
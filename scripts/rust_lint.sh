@@ -41,3 +41,10 @@ cargo sort --grouped --workspace $CHECK_ARG
 
 # Check for unused rust dependencies.
 cargo machete
+
+# Make sure the testing features of the VM (e.g., native call counting) do not leak
+# into production crates through their dependency on the e2e test harness.
+if cargo tree -p aptos-executor-service -e features,no-dev -i aptos-vm | grep -q 'aptos-vm feature "testing"'; then
+    echo "aptos-executor-service must not enable the testing feature of aptos-vm!"
+    exit 1
+fi